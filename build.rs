@@ -0,0 +1,33 @@
+use std::process::Command;
+
+// Rebuild when migrations change so sqlx::migrate!() embeds the latest set
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+
+    // Build identification for GET /version. Docker builds have no .git directory,
+    // so GIT_SHA and BUILD_DATE can be passed in as build args instead.
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    println!("cargo:rerun-if-env-changed=BUILD_DATE");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+
+    let git_sha = std::env::var("GIT_SHA")
+        .ok()
+        .filter(|sha| !sha.is_empty())
+        .or_else(|| {
+            let output = Command::new("git").args(["rev-parse", "--short", "HEAD"]).output().ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let build_time = std::env::var("BUILD_DATE")
+        .ok()
+        .filter(|date| !date.is_empty())
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+
+    println!("cargo:rustc-env=GIT_SHA={}", git_sha);
+    println!("cargo:rustc-env=BUILD_TIME={}", build_time);
+}
@@ -52,8 +52,9 @@ for i in $(seq 1 $NUM_ITEMS); do
     echo "  Traceparent: $TRACEPARENT"
     echo -e "  Value: $VALUE"
     
-    RESPONSE=$(curl -s -X POST http://localhost:3000/items \
+    RESPONSE=$(curl -s -X POST http://localhost:3000/v1/items \
         -H "Content-Type: application/json" \
+        -H "X-Tenant-Id: demo" \
         -H "traceparent: $TRACEPARENT" \
         -d "{\"name\": \"Demo Item $i\", \"value\": $VALUE}")
     
@@ -81,8 +82,9 @@ echo -e "${BLUE}View metrics:${NC}"
 echo "  http://localhost:3000/metrics"
 echo ""
 echo -e "${YELLOW}Try creating an item with tracing:${NC}"
-echo -e "${NC}  curl -X POST http://localhost:3000/items \\${NC}"
+echo -e "${NC}  curl -X POST http://localhost:3000/v1/items \\${NC}"
 echo -e "${NC}    -H \"Content-Type: application/json\" \\${NC}"
+echo -e "${NC}    -H \"X-Tenant-Id: demo\" \\${NC}"
 echo -e "${NC}    -H \"traceparent: 00-\$(uuidgen | tr -d '-')-\$(uuidgen | cut -c1-16)-01\" \\${NC}"
 echo -e "${NC}    -d '{\"name\": \"My Item\", \"value\": 42}'${NC}"
 echo ""
@@ -0,0 +1,14 @@
+-- Transactional outbox: events are written alongside item changes and
+-- published to Kafka asynchronously by the background outbox publisher.
+CREATE TABLE IF NOT EXISTS outbox (
+    id BIGSERIAL PRIMARY KEY,
+    item_id TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    trace_id TEXT,
+    span_id TEXT,
+    trace_state TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    published_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS outbox_unpublished_idx ON outbox (id) WHERE published_at IS NULL;
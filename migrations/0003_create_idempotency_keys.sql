@@ -0,0 +1,7 @@
+-- Maps client-supplied Idempotency-Key headers to the item they created.
+-- Rows older than the configured TTL are treated as expired and may be reused.
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    key TEXT PRIMARY KEY,
+    item_id UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
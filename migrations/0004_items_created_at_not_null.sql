@@ -0,0 +1,3 @@
+-- created_at is decoded as a non-optional timestamp, so forbid NULLs
+UPDATE items SET created_at = NOW() WHERE created_at IS NULL;
+ALTER TABLE items ALTER COLUMN created_at SET NOT NULL;
@@ -0,0 +1,3 @@
+-- Last modification time of an item. Maintained explicitly by the update query
+-- (SET updated_at = NOW()) rather than by a trigger.
+ALTER TABLE items ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW();
@@ -0,0 +1,2 @@
+-- W3C baggage captured with the request, re-sent as the Kafka baggage header
+ALTER TABLE outbox ADD COLUMN IF NOT EXISTS baggage TEXT;
@@ -0,0 +1,17 @@
+-- Owning tenant of each item, from the X-Tenant-Id header. Rows written before
+-- tenancy existed belong to the 'default' tenant.
+ALTER TABLE items ADD COLUMN IF NOT EXISTS tenant_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE items ALTER COLUMN tenant_id DROP DEFAULT;
+
+-- Every item query is scoped by tenant; newest-first listing is the default order
+CREATE INDEX IF NOT EXISTS items_tenant_created_at_idx ON items (tenant_id, created_at);
+
+-- Idempotency keys are per tenant, so two tenants may use the same key
+ALTER TABLE idempotency_keys ADD COLUMN IF NOT EXISTS tenant_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE idempotency_keys ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE idempotency_keys DROP CONSTRAINT IF EXISTS idempotency_keys_pkey;
+ALTER TABLE idempotency_keys ADD PRIMARY KEY (tenant_id, key);
+
+-- Name uniqueness becomes per tenant; the index is recreated on (tenant_id, name)
+-- at startup when ENFORCE_UNIQUE_NAMES is on
+DROP INDEX IF EXISTS items_name_unique;
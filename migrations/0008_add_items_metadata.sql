@@ -0,0 +1,3 @@
+-- Optional client-supplied JSON object attached to an item. The API only accepts
+-- objects and caps their serialized size (METADATA_MAX_BYTES).
+ALTER TABLE items ADD COLUMN IF NOT EXISTS metadata JSONB;
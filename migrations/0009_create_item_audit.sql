@@ -0,0 +1,17 @@
+-- Every change to an item, written in the same transaction as the change. Rows
+-- outlive the item, so there is no foreign key: a deleted item keeps its history.
+-- old_value and new_value are JSON snapshots of the whole item before and after.
+CREATE TABLE IF NOT EXISTS item_audit (
+    id BIGSERIAL PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    item_id UUID NOT NULL,
+    action TEXT NOT NULL,
+    old_value JSONB,
+    new_value JSONB,
+    actor TEXT NOT NULL,
+    request_id TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- History is read per item, in insertion order
+CREATE INDEX IF NOT EXISTS item_audit_item_idx ON item_audit (tenant_id, item_id, id);
@@ -0,0 +1,200 @@
+use anyhow::Context;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+// Algorithms accepted for JWKS keys. HMAC is excluded so a public key can never be
+// used as a shared secret.
+const JWKS_ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+];
+
+// Claims of a verified bearer token, stored in the request extensions
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthClaims {
+    pub sub: String,
+    // OAuth 2 space-separated scope list
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+impl AuthClaims {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scope
+            .as_deref()
+            .is_some_and(|scopes| scopes.split_whitespace().any(|granted| granted == scope))
+    }
+}
+
+enum Keys {
+    // JWT_SECRET, for HS256 tokens
+    Secret(DecodingKey),
+    // JWT_JWKS_URL keys by kid, swapped out on refresh
+    Jwks(RwLock<HashMap<String, DecodingKey>>),
+}
+
+// Verifies bearer JWTs against a shared secret or the keys published at a JWKS URL.
+// exp is required; iss and aud are checked only when JWT_ISSUER / JWT_AUDIENCE are set.
+pub struct JwtVerifier {
+    keys: Keys,
+    validation: Validation,
+}
+
+impl JwtVerifier {
+    pub fn from_secret(secret: &str, issuer: Option<&str>, audience: Option<&str>) -> Self {
+        JwtVerifier {
+            keys: Keys::Secret(DecodingKey::from_secret(secret.as_bytes())),
+            validation: validation(&[Algorithm::HS256], issuer, audience),
+        }
+    }
+
+    pub fn from_jwks(jwks: &JwkSet, issuer: Option<&str>, audience: Option<&str>) -> anyhow::Result<Self> {
+        Ok(JwtVerifier {
+            keys: Keys::Jwks(RwLock::new(jwks_keys(jwks)?)),
+            validation: validation(JWKS_ALGORITHMS, issuer, audience),
+        })
+    }
+
+    // Replace the JWKS keys after a refresh; a no-op for a shared secret
+    pub fn update_jwks(&self, jwks: &JwkSet) -> anyhow::Result<()> {
+        if let Keys::Jwks(keys) = &self.keys {
+            *keys.write().unwrap() = jwks_keys(jwks)?;
+        }
+        Ok(())
+    }
+
+    pub fn verify(&self, token: &str) -> anyhow::Result<AuthClaims> {
+        let data = match &self.keys {
+            Keys::Secret(key) => jsonwebtoken::decode::<AuthClaims>(token, key, &self.validation)?,
+            Keys::Jwks(keys) => {
+                let kid = jsonwebtoken::decode_header(token)?
+                    .kid
+                    .context("token header has no kid")?;
+                let keys = keys.read().unwrap();
+                let key = keys.get(&kid).with_context(|| format!("no JWKS key with kid {kid}"))?;
+                jsonwebtoken::decode::<AuthClaims>(token, key, &self.validation)?
+            }
+        };
+        Ok(data.claims)
+    }
+}
+
+fn validation(algorithms: &[Algorithm], issuer: Option<&str>, audience: Option<&str>) -> Validation {
+    let mut validation = Validation::new(algorithms[0]);
+    validation.algorithms = algorithms.to_vec();
+    if let Some(issuer) = issuer {
+        validation.set_issuer(&[issuer]);
+    }
+    // Without a configured audience a token's aud claim is not checked
+    validation.validate_aud = audience.is_some();
+    if let Some(audience) = audience {
+        validation.set_audience(&[audience]);
+    }
+    validation
+}
+
+// Usable keys of a JWKS by kid. Keys without a kid cannot be selected and are skipped.
+fn jwks_keys(jwks: &JwkSet) -> anyhow::Result<HashMap<String, DecodingKey>> {
+    let mut keys = HashMap::new();
+    for jwk in &jwks.keys {
+        let Some(kid) = &jwk.common.key_id else {
+            continue;
+        };
+        let key = DecodingKey::from_jwk(jwk).with_context(|| format!("JWKS key {kid} is not usable"))?;
+        keys.insert(kid.clone(), key);
+    }
+    anyhow::ensure!(!keys.is_empty(), "JWKS has no keys with a kid");
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde::Serialize;
+
+    const SECRET: &str = "an-hs256-secret-of-at-least-32-bytes";
+
+    #[derive(Serialize)]
+    struct Claims<'a> {
+        sub: &'a str,
+        exp: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        scope: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        iss: Option<&'a str>,
+    }
+
+    fn now() -> u64 {
+        chrono::Utc::now().timestamp() as u64
+    }
+
+    fn sign(claims: &Claims, secret: &str) -> String {
+        jsonwebtoken::encode(&Header::default(), claims, &EncodingKey::from_secret(secret.as_bytes())).unwrap()
+    }
+
+    fn claims(sub: &str) -> Claims<'_> {
+        Claims {
+            sub,
+            exp: now() + 300,
+            scope: Some("items:read items:write"),
+            iss: None,
+        }
+    }
+
+    #[test]
+    fn test_verify_signed_token() {
+        let verifier = JwtVerifier::from_secret(SECRET, None, None);
+        let claims = verifier.verify(&sign(&claims("alice"), SECRET)).unwrap();
+        assert_eq!(claims.sub, "alice");
+        assert!(claims.has_scope("items:write"));
+        assert!(!claims.has_scope("items"));
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_and_foreign_tokens() {
+        let verifier = JwtVerifier::from_secret(SECRET, None, None);
+        let token = sign(&claims("alice"), SECRET);
+
+        // Swap in the payload of a token for another subject, keeping alice's signature
+        let forged = sign(&claims("mallory"), "attacker-chosen-secret-32-bytes!!");
+        let mut parts: Vec<&str> = token.split('.').collect();
+        parts[1] = forged.split('.').nth(1).unwrap();
+        assert!(verifier.verify(&parts.join(".")).is_err());
+
+        assert!(verifier.verify(&forged).is_err());
+        assert!(verifier.verify("not.a.jwt").is_err());
+    }
+
+    #[test]
+    fn test_verify_checks_expiry_and_issuer() {
+        let verifier = JwtVerifier::from_secret(SECRET, Some("https://issuer.example"), None);
+        let expired = Claims {
+            exp: now() - 3600,
+            iss: Some("https://issuer.example"),
+            ..claims("alice")
+        };
+        assert!(verifier.verify(&sign(&expired, SECRET)).is_err());
+
+        let wrong_issuer = Claims {
+            iss: Some("https://other.example"),
+            ..claims("alice")
+        };
+        assert!(verifier.verify(&sign(&wrong_issuer, SECRET)).is_err());
+
+        let valid = Claims {
+            iss: Some("https://issuer.example"),
+            ..claims("alice")
+        };
+        assert!(verifier.verify(&sign(&valid, SECRET)).is_ok());
+    }
+}
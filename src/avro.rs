@@ -0,0 +1,343 @@
+use anyhow::Context;
+use apache_avro::types::{Record, Value};
+use apache_avro::Schema;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::models::ItemEvent;
+
+// Avro schema for ItemEvent, registered under the "<topic>-value" subject
+pub const ITEM_EVENT_SCHEMA: &str = include_str!("../schemas/item_event.avsc");
+
+// Confluent wire format: magic byte 0, then the 4-byte big-endian schema id
+const MAGIC_BYTE: u8 = 0;
+
+#[derive(Deserialize)]
+struct RegisterSchemaResponse {
+    id: u32,
+}
+
+#[derive(Deserialize)]
+struct SchemaResponse {
+    schema: String,
+}
+
+// Encodes item events as Avro in the Confluent Schema Registry wire format
+#[derive(Debug)]
+pub struct AvroEncoder {
+    schema: Schema,
+    schema_ids: HashMap<String, u32>,
+}
+
+impl AvroEncoder {
+    // Register the event schema for each topic and remember the returned ids
+    pub async fn register(registry_url: &str, topics: &[&str]) -> anyhow::Result<Self> {
+        let schema = Schema::parse_str(ITEM_EVENT_SCHEMA).context("Invalid item event schema")?;
+        let client = reqwest::Client::new();
+
+        let mut schema_ids = HashMap::new();
+        for topic in topics {
+            let url = format!("{}/subjects/{}-value/versions", registry_url.trim_end_matches('/'), topic);
+            let response: RegisterSchemaResponse = client
+                .post(&url)
+                .header(reqwest::header::CONTENT_TYPE, "application/vnd.schemaregistry.v1+json")
+                .json(&serde_json::json!({ "schema": ITEM_EVENT_SCHEMA }))
+                .send()
+                .await
+                .with_context(|| format!("Failed to reach schema registry at {}", url))?
+                .error_for_status()
+                .with_context(|| format!("Schema registration failed for topic {}", topic))?
+                .json()
+                .await
+                .context("Invalid schema registry response")?;
+            schema_ids.insert(topic.to_string(), response.id);
+        }
+
+        Ok(AvroEncoder { schema, schema_ids })
+    }
+
+    pub fn encode(&self, topic: &str, event: &ItemEvent) -> anyhow::Result<Vec<u8>> {
+        let schema_id = *self
+            .schema_ids
+            .get(topic)
+            .with_context(|| format!("No schema registered for topic {}", topic))?;
+        let datum = apache_avro::to_avro_datum(&self.schema, to_avro_value(&self.schema, event)?)
+            .context("Failed to encode item event as Avro")?;
+
+        let mut payload = Vec::with_capacity(5 + datum.len());
+        payload.push(MAGIC_BYTE);
+        payload.extend_from_slice(&schema_id.to_be_bytes());
+        payload.extend_from_slice(&datum);
+        Ok(payload)
+    }
+}
+
+// Decodes item events in the Confluent wire format. Writer schemas are fetched from
+// the registry by the id in each message and cached; values are resolved against
+// ITEM_EVENT_SCHEMA, so events written with an older compatible schema still decode.
+#[derive(Debug)]
+pub struct AvroDecoder {
+    registry_url: String,
+    client: reqwest::Client,
+    reader_schema: Schema,
+    writer_schemas: RwLock<HashMap<u32, Schema>>,
+}
+
+impl AvroDecoder {
+    // Fetch the latest schema registered for the topic, so an unreachable registry or a
+    // topic without an Avro schema fails at startup instead of on every event
+    pub async fn connect(registry_url: &str, topic: &str) -> anyhow::Result<Self> {
+        let decoder = AvroDecoder {
+            registry_url: registry_url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+            reader_schema: Schema::parse_str(ITEM_EVENT_SCHEMA).context("Invalid item event schema")?,
+            writer_schemas: RwLock::new(HashMap::new()),
+        };
+        let url = format!("{}/subjects/{}-value/versions/latest", decoder.registry_url, topic);
+        let response: RegisterSchemaResponse = decoder.get(&url).await?;
+        decoder.writer_schema(response.id).await?;
+        Ok(decoder)
+    }
+
+    pub async fn decode(&self, payload: &[u8]) -> anyhow::Result<ItemEvent> {
+        let (schema_id, datum) = split_frame(payload)?;
+        let writer_schema = self.writer_schema(schema_id).await?;
+        decode_datum(&writer_schema, &self.reader_schema, datum)
+    }
+
+    async fn writer_schema(&self, id: u32) -> anyhow::Result<Schema> {
+        if let Some(schema) = self.writer_schemas.read().unwrap().get(&id) {
+            return Ok(schema.clone());
+        }
+        let url = format!("{}/schemas/ids/{}", self.registry_url, id);
+        let response: SchemaResponse = self.get(&url).await?;
+        let schema = Schema::parse_str(&response.schema).with_context(|| format!("Invalid schema {} in registry", id))?;
+        self.writer_schemas.write().unwrap().insert(id, schema.clone());
+        Ok(schema)
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, url: &str) -> anyhow::Result<T> {
+        self.client
+            .get(url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach schema registry at {}", url))?
+            .error_for_status()
+            .with_context(|| format!("Schema registry lookup failed: {}", url))?
+            .json()
+            .await
+            .context("Invalid schema registry response")
+    }
+}
+
+// Schema id and Avro datum of a Confluent-framed payload
+fn split_frame(payload: &[u8]) -> anyhow::Result<(u32, &[u8])> {
+    match payload {
+        [MAGIC_BYTE, a, b, c, d, datum @ ..] => Ok((u32::from_be_bytes([*a, *b, *c, *d]), datum)),
+        _ => anyhow::bail!("payload is not in the Confluent Avro wire format"),
+    }
+}
+
+fn decode_datum(writer_schema: &Schema, reader_schema: &Schema, mut datum: &[u8]) -> anyhow::Result<ItemEvent> {
+    let value = apache_avro::from_avro_datum(writer_schema, &mut datum, Some(reader_schema))
+        .context("Failed to decode Avro item event")?;
+    from_avro_value(value)
+}
+
+// Optional schema fields are ["null", T] unions
+fn optional(value: Option<Value>) -> Value {
+    match value {
+        Some(value) => Value::Union(1, Box::new(value)),
+        None => Value::Union(0, Box::new(Value::Null)),
+    }
+}
+
+fn to_avro_value(schema: &Schema, event: &ItemEvent) -> anyhow::Result<Value> {
+    let mut record = Record::new(schema).context("Item event schema is not a record")?;
+
+    let (symbol, index) = match event {
+        ItemEvent::Created { .. } => ("item_created", 0),
+        ItemEvent::Updated { .. } => ("item_updated", 1),
+        ItemEvent::Deleted { .. } => ("item_deleted", 2),
+    };
+    record.put("type", Value::Enum(index, symbol.to_string()));
+    record.put("id", Value::String(event.item_id().to_string()));
+    record.put("tenant_id", Value::String(event.tenant_id().to_string()));
+    let replayed = matches!(event, ItemEvent::Created { replayed: true, .. });
+    record.put("replayed", Value::Boolean(replayed));
+
+    // Metadata is free-form JSON, so it travels as a JSON string rather than a record
+    let metadata = match event {
+        ItemEvent::Created { metadata: Some(metadata), .. } => Some(Value::String(metadata.to_string())),
+        _ => None,
+    };
+    record.put("metadata", optional(metadata));
+
+    match event {
+        ItemEvent::Created { name, value, created_at, request_id, .. }
+        | ItemEvent::Updated { name, value, created_at, request_id, .. } => {
+            record.put("name", optional(Some(Value::String(name.clone()))));
+            record.put("value", optional(Some(Value::Long(*value))));
+            record.put("created_at", optional(Some(Value::String(created_at.to_rfc3339()))));
+            record.put("deleted_at", optional(None));
+            record.put("request_id", optional(request_id.clone().map(Value::String)));
+        }
+        ItemEvent::Deleted { deleted_at, request_id, .. } => {
+            record.put("name", optional(None));
+            record.put("value", optional(None));
+            record.put("created_at", optional(None));
+            record.put("deleted_at", optional(Some(Value::String(deleted_at.clone()))));
+            record.put("request_id", optional(request_id.clone().map(Value::String)));
+        }
+    }
+
+    Ok(record.into())
+}
+
+// Inverse of to_avro_value
+fn from_avro_value(value: Value) -> anyhow::Result<ItemEvent> {
+    let Value::Record(fields) = value else {
+        anyhow::bail!("item event is not a record");
+    };
+    let mut fields: HashMap<String, Value> = fields.into_iter().collect();
+    // Unwraps ["null", T] unions; a missing field or null reads as None
+    let mut field = |name: &str| match fields.remove(name) {
+        Some(Value::Union(_, value)) => Some(*value),
+        Some(Value::Null) | None => None,
+        Some(value) => Some(value),
+    };
+    fn string(value: Option<Value>) -> Option<String> {
+        match value {
+            Some(Value::String(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    let Some(Value::Enum(_, event_type)) = field("type") else {
+        anyhow::bail!("item event has no type");
+    };
+    let id = string(field("id")).context("item event has no id")?;
+    let tenant_id = string(field("tenant_id")).unwrap_or_else(|| crate::models::DEFAULT_TENANT.to_string());
+    let request_id = string(field("request_id"));
+
+    if event_type == "item_deleted" {
+        let deleted_at = string(field("deleted_at")).context("item_deleted event has no deleted_at")?;
+        return Ok(ItemEvent::Deleted { id, tenant_id, deleted_at, request_id });
+    }
+
+    let name = string(field("name")).context("item event has no name")?;
+    let Some(Value::Long(value)) = field("value") else {
+        anyhow::bail!("item event has no value");
+    };
+    let created_at = string(field("created_at"))
+        .context("item event has no created_at")?
+        .parse()
+        .context("item event created_at is not RFC 3339")?;
+    match event_type.as_str() {
+        "item_created" => Ok(ItemEvent::Created {
+            id,
+            tenant_id,
+            name,
+            value,
+            created_at,
+            request_id,
+            replayed: matches!(field("replayed"), Some(Value::Boolean(true))),
+            metadata: string(field("metadata"))
+                .map(|metadata| serde_json::from_str(&metadata))
+                .transpose()
+                .context("item event metadata is not JSON")?,
+        }),
+        "item_updated" => Ok(ItemEvent::Updated { id, tenant_id, name, value, created_at, request_id }),
+        other => anyhow::bail!("unknown item event type {}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoder(schema_id: u32) -> AvroEncoder {
+        AvroEncoder {
+            schema: Schema::parse_str(ITEM_EVENT_SCHEMA).unwrap(),
+            schema_ids: HashMap::from([("items.created".to_string(), schema_id)]),
+        }
+    }
+
+    #[test]
+    fn test_encode_uses_confluent_wire_format() {
+        let event = ItemEvent::Created {
+            id: "123".to_string(),
+            tenant_id: "acme".to_string(),
+            name: "Test".to_string(),
+            value: 42,
+            created_at: "2024-01-01T00:00:00Z".parse().unwrap(),
+            request_id: None,
+            replayed: false,
+            metadata: Some(serde_json::json!({"tags": ["a"]})),
+        };
+
+        let payload = encoder(7).encode("items.created", &event).unwrap();
+        assert_eq!(&payload[..5], &[0, 0, 0, 0, 7]);
+
+        let schema = Schema::parse_str(ITEM_EVENT_SCHEMA).unwrap();
+        let decoded = apache_avro::from_avro_datum(&schema, &mut &payload[5..], None).unwrap();
+        let Value::Record(fields) = decoded else {
+            panic!("expected record, got {:?}", decoded);
+        };
+        assert_eq!(fields[0], ("type".to_string(), Value::Enum(0, "item_created".to_string())));
+        assert_eq!(fields[1], ("id".to_string(), Value::String("123".to_string())));
+        assert_eq!(fields[3], ("value".to_string(), Value::Union(1, Box::new(Value::Long(42)))));
+        assert_eq!(fields[8], ("tenant_id".to_string(), Value::String("acme".to_string())));
+        assert_eq!(
+            fields[9],
+            ("metadata".to_string(), Value::Union(1, Box::new(Value::String(r#"{"tags":["a"]}"#.to_string()))))
+        );
+    }
+
+    #[test]
+    fn test_decode_round_trips_encoded_events() {
+        let schema = Schema::parse_str(ITEM_EVENT_SCHEMA).unwrap();
+        let created = ItemEvent::Created {
+            id: "123".to_string(),
+            tenant_id: "acme".to_string(),
+            name: "Test".to_string(),
+            value: 42,
+            created_at: "2024-01-01T00:00:00Z".parse().unwrap(),
+            request_id: Some("req-1".to_string()),
+            replayed: true,
+            metadata: Some(serde_json::json!({"tags": ["a"]})),
+        };
+        let deleted = ItemEvent::Deleted {
+            id: "123".to_string(),
+            tenant_id: "acme".to_string(),
+            deleted_at: "2024-01-02T00:00:00Z".to_string(),
+            request_id: None,
+        };
+
+        for event in [created, deleted] {
+            let payload = encoder(7).encode("items.created", &event).unwrap();
+            let (schema_id, datum) = split_frame(&payload).unwrap();
+            assert_eq!(schema_id, 7);
+            let decoded = decode_datum(&schema, &schema, datum).unwrap();
+            assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&event).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_split_frame_rejects_json_payloads() {
+        assert!(split_frame(br#"{"type":"item_created"}"#).is_err());
+        assert!(split_frame(&[0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_encode_unknown_topic() {
+        let event = ItemEvent::Deleted {
+            id: "123".to_string(),
+            tenant_id: "acme".to_string(),
+            deleted_at: "2024-01-01T00:00:00Z".to_string(),
+            request_id: None,
+        };
+        assert!(encoder(1).encode("items.deleted", &event).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+// Reference consumer for item created events.
+// Subscribes to the created topic, continues the producer's trace from the
+// traceparent header and commits offsets only after an event was handled.
+// Payloads are decoded as KAFKA_SERIALIZATION says the API publishes them.
+
+use anyhow::Context;
+use home_task::telemetry::{kafka_parent_context, KafkaHeaderExtractor};
+use home_task::{AvroDecoder, Config, ItemEvent, KafkaSerialization, LogFormat};
+use opentelemetry::KeyValue;
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_sdk::resource::Resource;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::message::{BorrowedMessage, Message};
+use tracing::{error, info, info_span, warn, Instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Registry as TracingRegistry};
+
+// Setup tracing with OpenTelemetry (returns provider to keep alive, None when
+// OTEL_ENABLED=false)
+fn setup_tracing(config: &Config) -> anyhow::Result<Option<opentelemetry_sdk::trace::SdkTracerProvider>> {
+    use opentelemetry_sdk::trace::BatchSpanProcessor;
+
+    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| "info".into());
+
+    let provider = if config.otel_enabled {
+        let exporter = home_task::telemetry::span_exporter(config)?;
+
+        Some(
+            opentelemetry_sdk::trace::SdkTracerProvider::builder()
+                .with_span_processor(BatchSpanProcessor::builder(exporter).build())
+                .with_sampler(home_task::telemetry::sampler(config))
+                .with_resource(
+                    Resource::builder()
+                        .with_attributes(vec![
+                            KeyValue::new("service.name", format!("{}-consumer", config.service_name)),
+                            KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+                        ])
+                        .build(),
+                )
+                .build(),
+        )
+    } else {
+        None
+    };
+
+    let telemetry_layer = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(format!("{}-consumer", config.service_name)))
+    });
+
+    let (text_layer, json_layer) = match config.log_format {
+        LogFormat::Text => (Some(tracing_subscriber::fmt::layer()), None),
+        LogFormat::Json => (
+            None,
+            Some(tracing_subscriber::fmt::layer().json().with_current_span(true)),
+        ),
+    };
+
+    TracingRegistry::default()
+        .with(env_filter)
+        .with(telemetry_layer)
+        .with(text_layer)
+        .with(json_layer)
+        .try_init()
+        .context("Failed to initialize tracing")?;
+
+    Ok(provider)
+}
+
+fn create_consumer(config: &Config) -> anyhow::Result<StreamConsumer> {
+    let consumer: StreamConsumer = ClientConfig::new()
+        .set("bootstrap.servers", &config.kafka_brokers)
+        .set("group.id", &config.kafka_consumer_group)
+        .set("auto.offset.reset", "earliest")
+        // Offsets are committed manually once an event has been processed
+        .set("enable.auto.commit", "false")
+        .create()
+        .context("Failed to create Kafka consumer")?;
+
+    consumer
+        .subscribe(&[config.kafka_topic_created.as_str()])
+        .context("Failed to subscribe to topic")?;
+
+    Ok(consumer)
+}
+
+// Payload format of the created topic, matching the API's KAFKA_SERIALIZATION
+enum EventDecoder {
+    Json,
+    Avro(AvroDecoder),
+}
+
+impl EventDecoder {
+    // With Avro the registry must serve the topic's schema, or the consumer would
+    // skip every event as undecodable; refuse to start instead
+    async fn new(config: &Config) -> anyhow::Result<Self> {
+        match config.kafka_serialization {
+            KafkaSerialization::Json => Ok(EventDecoder::Json),
+            KafkaSerialization::Avro => {
+                let registry_url = config
+                    .schema_registry_url
+                    .as_deref()
+                    .context("SCHEMA_REGISTRY_URL is required when KAFKA_SERIALIZATION is avro")?;
+                let decoder = AvroDecoder::connect(registry_url, &config.kafka_topic_created)
+                    .await
+                    .context("Cannot decode Avro events")?;
+                info!(registry = %registry_url, "Decoding events as Avro");
+                Ok(EventDecoder::Avro(decoder))
+            }
+        }
+    }
+
+    async fn decode(&self, payload: &[u8]) -> anyhow::Result<ItemEvent> {
+        match self {
+            EventDecoder::Json => Ok(serde_json::from_slice(payload)?),
+            EventDecoder::Avro(decoder) => decoder.decode(payload).await,
+        }
+    }
+}
+
+// Handle a single message inside a span parented to the producer's trace
+async fn process_message(message: &BorrowedMessage<'_>, decoder: &EventDecoder) {
+    let span = info_span!(
+        "process_item_event",
+        topic = message.topic(),
+        partition = message.partition(),
+        offset = message.offset(),
+        producer_version = KafkaHeaderExtractor(message).get("producer_version"),
+    );
+    let _ = span.set_parent(kafka_parent_context(message));
+
+    async {
+        let Some(payload) = message.payload() else {
+            warn!("Skipping message without payload");
+            return;
+        };
+
+        match decoder.decode(payload).await {
+            Ok(ItemEvent::Created { id, tenant_id, name, value, request_id, replayed, .. }) => {
+                info!(item_id = %id, tenant_id = %tenant_id, item_name = %name, item_value = value, request_id = ?request_id, replayed, "Processed item created event");
+            }
+            Ok(other) => {
+                warn!(item_id = other.item_id(), "Ignoring unexpected event type");
+            }
+            Err(e) => {
+                // Poison messages are logged and skipped so they don't block the partition
+                error!(error = format!("{:#}", e), "Failed to deserialize item event");
+            }
+        }
+    }
+    .instrument(span)
+    .await
+}
+
+// Resolve on Ctrl-C or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.ok();
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+
+    let config = Config::load().context("Invalid configuration")?;
+    config.validate().context("Invalid configuration")?;
+
+    // Initialize tracing - keep provider alive
+    let otel_provider = setup_tracing(&config)?;
+
+    let decoder = EventDecoder::new(&config).await?;
+    let consumer = create_consumer(&config)?;
+    info!(
+        topic = %config.kafka_topic_created,
+        group = %config.kafka_consumer_group,
+        "Consumer started"
+    );
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => {
+                info!("Shutdown signal received");
+                break;
+            }
+            result = consumer.recv() => match result {
+                Ok(message) => {
+                    process_message(&message, &decoder).await;
+                    if let Err(e) = consumer.commit_message(&message, CommitMode::Async) {
+                        error!(error = %e, "Failed to commit offset");
+                    }
+                }
+                Err(e) => error!(error = %e, "Kafka consume error"),
+            },
+        }
+    }
+
+    // Flush pending offset commits before leaving the group
+    if let Err(e) = consumer.commit_consumer_state(CommitMode::Sync) {
+        warn!(error = %e, "Failed to commit final offsets");
+    }
+    consumer.unsubscribe();
+
+    if let Some(provider) = otel_provider
+        && let Err(e) = provider.shutdown()
+    {
+        warn!(error = %e, "Failed to flush traces");
+    }
+
+    info!("Consumer stopped");
+    Ok(())
+}
@@ -0,0 +1,86 @@
+// Container healthcheck: exits 0 when the local API answers its probe with 200 and
+// 1 otherwise, for use as a Docker HEALTHCHECK command.
+// Deliberately std-only, with no async runtime or HTTP client, so a probe stays cheap.
+
+use home_task::Config;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+use std::process::ExitCode;
+use std::time::Duration;
+
+// Readiness by default. HEALTHCHECK_PATH=/health probes liveness instead, for
+// orchestrators that restart unhealthy containers: a restart fixes a lost database
+// connection but not a slow downstream consumer, which /readyz also reports.
+const DEFAULT_PROBE_PATH: &str = "/readyz";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Where to reach the server: its bind address, or loopback when it binds every interface
+fn probe_addr(config: &Config) -> SocketAddr {
+    let ip = match config.bind_address.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        Ok(IpAddr::V6(ip)) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        Ok(ip) => ip,
+        Err(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+    };
+    SocketAddr::new(ip, config.port)
+}
+
+// Path to probe: HEALTHCHECK_PATH when it is an absolute path, else DEFAULT_PROBE_PATH
+fn probe_path() -> String {
+    std::env::var("HEALTHCHECK_PATH")
+        .ok()
+        .filter(|path| path.starts_with('/') && !path.contains(char::is_whitespace))
+        .unwrap_or_else(|| DEFAULT_PROBE_PATH.to_string())
+}
+
+// Status code of a GET to path
+fn probe(addr: SocketAddr, path: &str) -> std::io::Result<u16> {
+    let mut stream = TcpStream::connect_timeout(&addr, PROBE_TIMEOUT)?;
+    stream.set_read_timeout(Some(PROBE_TIMEOUT))?;
+    stream.set_write_timeout(Some(PROBE_TIMEOUT))?;
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: {addr}\r\nUser-Agent: home-task-healthcheck\r\nConnection: close\r\n\r\n"
+    )?;
+
+    // Only the status line matters, e.g. "HTTP/1.1 200 OK"
+    let mut head = [0u8; 32];
+    let mut read = 0;
+    while read < head.len() {
+        match stream.read(&mut head[read..])? {
+            0 => break,
+            n => read += n,
+        }
+    }
+    String::from_utf8_lossy(&head[..read])
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "malformed status line"))
+}
+
+fn main() -> ExitCode {
+    dotenvy::dotenv().ok();
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("healthcheck: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let addr = probe_addr(&config);
+    let path = probe_path();
+    match probe(addr, &path) {
+        Ok(200) => ExitCode::SUCCESS,
+        Ok(status) => {
+            eprintln!("healthcheck: {}{} answered {}", addr, path, status);
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("healthcheck: {}{} unreachable: {}", addr, path, e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use axum::{
+    extract::rejection::JsonRejection,
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tracing::error;
+use utoipa::ToSchema;
+
+use crate::models::ValidationError;
+use crate::repository::UNIQUE_NAME_INDEX;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+    pub code: String,
+    // Every field problem when a request failed validation on more than one check
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub code: String,
+    pub message: String,
+}
+
+impl From<&ValidationError> for FieldError {
+    fn from(e: &ValidationError) -> Self {
+        FieldError {
+            field: e.field().map(str::to_string),
+            code: e.code().to_string(),
+            message: e.to_string(),
+        }
+    }
+}
+
+// Application error returned by handlers, rendered as a JSON ErrorResponse
+#[derive(Debug)]
+pub enum AppError {
+    Validation(ValidationError),
+    // Non-empty list of every problem found in a request body
+    InvalidRequest(Vec<ValidationError>),
+    // Body that is not JSON or does not fit the request type
+    InvalidBody(String),
+    // Body sent without a JSON Content-Type
+    UnsupportedMediaType,
+    // Body over MAX_BODY_BYTES
+    PayloadTooLarge,
+    NotFound,
+    // No route matched the request path
+    RouteNotFound,
+    // The route exists but not for this method
+    MethodNotAllowed,
+    DuplicateName,
+    // Live idempotency key whose item could not be found
+    IdempotencyConflict,
+    Unauthorized,
+    // Valid bearer token without the scope the route needs
+    Forbidden,
+    Database(sqlx::Error),
+    // Server-side failure outside the database and Kafka
+    Internal(String),
+    Kafka(anyhow::Error),
+    RateLimited { retry_after_secs: u64 },
+    Overloaded,
+}
+
+impl AppError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::Validation(_) | AppError::InvalidRequest(_) | AppError::InvalidBody(_) => StatusCode::BAD_REQUEST,
+            AppError::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::NotFound | AppError::RouteNotFound => StatusCode::NOT_FOUND,
+            AppError::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            AppError::DuplicateName | AppError::IdempotencyConflict => StatusCode::CONFLICT,
+            AppError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden => StatusCode::FORBIDDEN,
+            AppError::Database(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Kafka(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Overloaded => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Validation(e) => e.code(),
+            // The first problem keeps the top-level code compatible with single-error responses
+            AppError::InvalidRequest(errors) => errors.first().map_or("invalid_request", |e| e.code()),
+            AppError::InvalidBody(_) => "invalid_body",
+            AppError::UnsupportedMediaType => "unsupported_media_type",
+            AppError::PayloadTooLarge => "payload_too_large",
+            AppError::NotFound => "not_found",
+            AppError::RouteNotFound => "route_not_found",
+            AppError::MethodNotAllowed => "method_not_allowed",
+            AppError::DuplicateName => "duplicate_name",
+            AppError::IdempotencyConflict => "idempotency_conflict",
+            AppError::Unauthorized => "unauthorized",
+            AppError::Forbidden => "forbidden",
+            AppError::Database(_) => "db_error",
+            AppError::Internal(_) => "internal_error",
+            AppError::Kafka(_) => "kafka_error",
+            AppError::RateLimited { .. } => "rate_limited",
+            AppError::Overloaded => "overloaded",
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Validation(e) => write!(f, "{}", e),
+            AppError::InvalidRequest(errors) => {
+                let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "{}", messages.join("; "))
+            }
+            AppError::InvalidBody(reason) => write!(f, "{}", reason),
+            AppError::UnsupportedMediaType => write!(f, "expected a request with Content-Type: application/json"),
+            AppError::PayloadTooLarge => write!(f, "request body is too large"),
+            AppError::NotFound => write!(f, "item not found"),
+            AppError::RouteNotFound => write!(f, "route not found"),
+            AppError::MethodNotAllowed => write!(f, "method not allowed"),
+            AppError::DuplicateName => write!(f, "an item with this name already exists"),
+            AppError::IdempotencyConflict => write!(f, "the idempotency key is in use by a request whose item no longer exists"),
+            AppError::Unauthorized => write!(f, "missing or invalid bearer token"),
+            AppError::Forbidden => write!(f, "bearer token lacks the required scope"),
+            AppError::Database(e) => write!(f, "Database error: {:?}", e),
+            AppError::Internal(reason) => write!(f, "Internal error: {}", reason),
+            AppError::Kafka(e) => write!(f, "Kafka error: {:?}", e),
+            AppError::RateLimited { retry_after_secs } => {
+                write!(f, "rate limit exceeded, retry after {} seconds", retry_after_secs)
+            }
+            AppError::Overloaded => write!(f, "server is at capacity, retry later"),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<ValidationError> for AppError {
+    fn from(e: ValidationError) -> Self {
+        AppError::Validation(e)
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            JsonRejection::MissingJsonContentType(_) => AppError::UnsupportedMediaType,
+            // The body limit surfaces as a failure to read the body
+            rejection if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => AppError::PayloadTooLarge,
+            rejection => AppError::InvalidBody(rejection.body_text()),
+        }
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        // Unique violation (23505) on the optional name index is a client conflict
+        if let sqlx::Error::Database(db) = &e
+            && db.is_unique_violation()
+            && db.constraint() == Some(UNIQUE_NAME_INDEX)
+        {
+            return AppError::DuplicateName;
+        }
+        AppError::Database(e)
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        if status.is_server_error() {
+            error!(error = %self, "Request failed");
+        }
+
+        let errors = match &self {
+            AppError::InvalidRequest(errors) => errors.iter().map(FieldError::from).collect(),
+            _ => Vec::new(),
+        };
+        let body = ErrorResponse {
+            error: self.to_string(),
+            code: self.code().to_string(),
+            errors,
+        };
+        let mut response = (status, Json(body)).into_response();
+
+        match self {
+            AppError::RateLimited { retry_after_secs } => {
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+            }
+            AppError::Overloaded => {
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, HeaderValue::from(1));
+            }
+            AppError::Unauthorized => {
+                response
+                    .headers_mut()
+                    .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            }
+            _ => {}
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_mapping() {
+        assert_eq!(AppError::Validation(ValidationError::NameEmpty).status(), StatusCode::BAD_REQUEST);
+        assert_eq!(AppError::NotFound.status(), StatusCode::NOT_FOUND);
+        assert_eq!(AppError::Database(sqlx::Error::RowNotFound).status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(AppError::Kafka(anyhow::anyhow!("down")).status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn test_validation_message() {
+        let err = AppError::Validation(ValidationError::NameEmpty);
+        assert_eq!(err.to_string(), "name cannot be empty");
+        assert_eq!(err.code(), "name_empty");
+    }
+
+    #[test]
+    fn test_rate_limited_sets_retry_after() {
+        let response = AppError::RateLimited { retry_after_secs: 2 }.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+    }
+
+    #[test]
+    fn test_unauthorized_challenges_bearer() {
+        let response = AppError::Unauthorized.into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+    }
+
+    #[test]
+    fn test_forbidden_does_not_challenge() {
+        let response = AppError::Forbidden.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(response.headers().get(header::WWW_AUTHENTICATE).is_none());
+    }
+
+    #[test]
+    fn test_overloaded_is_service_unavailable() {
+        let response = AppError::Overloaded.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+    }
+
+    #[test]
+    fn test_invalid_request_lists_every_error() {
+        let err = AppError::InvalidRequest(vec![
+            ValidationError::NameEmpty,
+            ValidationError::ValueOutOfRange { min: 0, max: 10 },
+        ]);
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(err.code(), "name_empty");
+        assert_eq!(err.to_string(), "name cannot be empty; value must be between 0 and 10");
+    }
+
+    #[test]
+    fn test_duplicate_name_is_conflict() {
+        let err = AppError::DuplicateName;
+        assert_eq!(err.status(), StatusCode::CONFLICT);
+        assert_eq!(err.code(), "duplicate_name");
+    }
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(AppError::NotFound.code(), "not_found");
+        assert_eq!(AppError::Database(sqlx::Error::RowNotFound).code(), "db_error");
+    }
+}
@@ -1,6 +1,21 @@
+pub mod auth;
+pub mod avro;
 pub mod config;
+pub mod error;
 pub mod models;
+pub mod rate_limit;
+pub mod repository;
+pub mod telemetry;
 
 // Re-export main items
-pub use config::Config;
-pub use models::{CreateItemRequest, Item, ItemEvent};
+pub use auth::{AuthClaims, JwtVerifier};
+pub use avro::{AvroDecoder, AvroEncoder};
+pub use config::{Config, ConfigError, KafkaAcks, KafkaKeyStrategy, KafkaSerialization, LogFormat, OtlpProtocol, ValueDistribution};
+pub use error::{AppError, ErrorResponse, FieldError};
+pub use rate_limit::RateLimiter;
+pub use repository::{CreateOutcome, EventContext, MemoryRepository, PgRepository, Repository};
+pub use models::{
+    if_none_match, items_to_csv, parse_item_id, validate_actor, validate_idempotency_key, validate_tenant_id, ANONYMOUS_ACTOR, DEFAULT_TENANT, EVENT_SCHEMA_VERSION, AuditAction, AuditEntry, CountItemsResponse, CreateItemParams, CreateItemRequest, DeleteByFilterRequest, DeleteByFilterResponse, IncrementItemRequest, Item, CreatedRange, ItemCursor, ItemEvent, ItemHistoryResponse,
+    ListItemsParams, ListItemsResponse, LookupItemsRequest, LookupItemsResponse, UpdateItemRequest, ValidationError,
+    W3CTraceContext,
+};
@@ -1,65 +1,1172 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::config::Config;
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Item {
     pub id: String,
+    pub tenant_id: String,
     pub name: String,
     pub value: i64,
-    pub created_at: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    // Client-supplied JSON object, set at creation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct CreateItemRequest {
     pub name: String,
+    // Also accepted as a string or an integral float, see deserialize_lenient_value
+    #[serde(default, deserialize_with = "deserialize_lenient_value")]
     pub value: Option<i64>,
+    // Must be a JSON object of at most METADATA_MAX_BYTES when serialized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct UpdateItemRequest {
+    pub name: Option<String>,
+    pub value: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct IncrementItemRequest {
+    pub delta: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CreateItemParams {
+    // Validate and echo the item that would be created, without persisting it
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListItemsParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub name_contains: Option<String>,
+    pub min_value: Option<i64>,
+    pub max_value: Option<i64>,
+    pub sort_by: Option<String>,
+    pub sort_dir: Option<String>,
+    pub cursor: Option<String>,
+    // RFC3339 bounds on created_at: from is inclusive, to is exclusive
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ListItemsResponse {
+    pub items: Vec<Item>,
+    pub total: i64,
+    // Pass back as `cursor` to fetch the next page; absent on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+// Half-open created_at window [from, to); either end may be open
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CreatedRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl CreatedRange {
+    pub fn contains(&self, created_at: DateTime<Utc>) -> bool {
+        self.from.is_none_or(|from| created_at >= from) && self.to.is_none_or(|to| created_at < to)
+    }
+}
+
+// Keyset position after the last item of a page in newest-first order. Clients treat
+// it as opaque; it is the base64url encoding of "<created_at>|<id>".
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: String,
+}
+
+impl ItemCursor {
+    pub fn after(item: &Item) -> Self {
+        ItemCursor {
+            created_at: item.created_at,
+            id: item.id.clone(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        use base64::Engine;
+        // Full precision, so no item sharing the timestamp's leading digits is skipped
+        let created_at = self.created_at.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(format!("{}|{}", created_at, self.id))
+    }
+
+    pub fn decode(raw: &str) -> Result<Self, ValidationError> {
+        use base64::Engine;
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(raw.trim())
+            .map_err(|_| ValidationError::InvalidCursor)?;
+        let text = String::from_utf8(bytes).map_err(|_| ValidationError::InvalidCursor)?;
+        let (created_at, id) = text.split_once('|').ok_or(ValidationError::InvalidCursor)?;
+
+        Ok(ItemCursor {
+            created_at: DateTime::parse_from_rfc3339(created_at)
+                .map_err(|_| ValidationError::InvalidCursor)?
+                .with_timezone(&Utc),
+            id: parse_item_id(id).map_err(|_| ValidationError::InvalidCursor)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CountItemsResponse {
+    pub count: i64,
+}
+
+// Kind of change an audit entry records
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl AuditAction {
+    // Value stored in item_audit.action
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Created => "created",
+            AuditAction::Updated => "updated",
+            AuditAction::Deleted => "deleted",
+        }
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created" => Ok(AuditAction::Created),
+            "updated" => Ok(AuditAction::Updated),
+            "deleted" => Ok(AuditAction::Deleted),
+            _ => Err(format!("unknown audit action {s}")),
+        }
+    }
+}
+
+// Actor recorded when a write carries no X-Actor header
+pub const ANONYMOUS_ACTOR: &str = "anonymous";
+
+// One change to an item. old_value and new_value are snapshots of the whole item:
+// a create has no old_value and a delete no new_value.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AuditEntry {
+    pub item_id: String,
+    pub action: AuditAction,
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: Option<serde_json::Value>,
+    pub actor: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+// Audit trail of one item, oldest change first
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ItemHistoryResponse {
+    pub id: String,
+    pub entries: Vec<AuditEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct LookupItemsRequest {
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct LookupItemsResponse {
+    pub items: Vec<Item>,
+    pub missing: Vec<String>,
+}
+
+// Admin purge of every item matching the list filters. Without confirm: true the
+// request is rejected, so an empty or mistyped filter cannot wipe a tenant by accident.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DeleteByFilterRequest {
+    pub name_contains: Option<String>,
+    pub min_value: Option<i64>,
+    pub max_value: Option<i64>,
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DeleteByFilterResponse {
+    pub deleted: u64,
+}
+
+// Accept a value as a JSON integer, an integral float such as 42.0, or a string holding
+// an integer such as "42", for loosely typed clients like form encoders. Fractional
+// and out-of-range numbers are rejected rather than truncated.
+fn deserialize_lenient_value<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::{Error, Unexpected, Visitor};
+
+    struct LenientValue;
+
+    impl<'de> Visitor<'de> for LenientValue {
+        type Value = Option<i64>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "an integer, or a string holding one")
+        }
+
+        fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+            Ok(Some(v))
+        }
+
+        fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+            i64::try_from(v)
+                .map(Some)
+                .map_err(|_| E::invalid_value(Unexpected::Unsigned(v), &self))
+        }
+
+        fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
+            // i64::MAX as f64 rounds up to 2^63, which is already out of range
+            if v.fract() == 0.0 && v >= i64::MIN as f64 && v < i64::MAX as f64 {
+                Ok(Some(v as i64))
+            } else {
+                Err(E::invalid_value(Unexpected::Float(v), &self))
+            }
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+            v.trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+        }
+    }
+
+    deserializer.deserialize_any(LenientValue)
 }
 
+// Maximum number of ids accepted by a single lookup request
+pub const LOOKUP_MAX_IDS: usize = 200;
+
+// Tenant that rows and events written before tenant_id existed belong to
+pub const DEFAULT_TENANT: &str = "default";
+
+fn default_tenant() -> String {
+    DEFAULT_TENANT.to_string()
+}
+
+// Version of the ItemEvent payload, sent as the schema_version Kafka header.
+// Adding optional fields keeps the version; renaming or removing one bumps it.
+pub const EVENT_SCHEMA_VERSION: &str = "1";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type")]
 pub enum ItemEvent {
     #[serde(rename = "item_created")]
-    Created { id: String, name: String, value: i64, created_at: String },
+    Created {
+        id: String,
+        #[serde(default = "default_tenant")]
+        tenant_id: String,
+        name: String,
+        value: i64,
+        created_at: DateTime<Utc>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>,
+        // Set when ops re-emitted the event for an existing item
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        replayed: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        metadata: Option<serde_json::Value>,
+    },
+    #[serde(rename = "item_updated")]
+    Updated {
+        id: String,
+        #[serde(default = "default_tenant")]
+        tenant_id: String,
+        name: String,
+        value: i64,
+        created_at: DateTime<Utc>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>,
+    },
+    #[serde(rename = "item_deleted")]
+    Deleted {
+        id: String,
+        #[serde(default = "default_tenant")]
+        tenant_id: String,
+        deleted_at: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>,
+    },
+}
+
+// W3C trace context captured from an inbound request and stored with outbox events
+#[derive(Debug, Clone)]
+pub struct W3CTraceContext {
+    pub trace_id: String,
+    pub span_id: String,
+    pub trace_state: Option<String>,
+    // Normalized W3C baggage header; malformed entries from the request are dropped
+    pub baggage: Option<String>,
+}
+
+// Validation failure carrying a machine-readable code for API clients
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    NameEmpty,
+    NameTooLong { max: usize },
+    ValueOutOfRange { min: i64, max: i64 },
+    InvalidLimit,
+    InvalidOffset,
+    BatchEmpty,
+    BatchTooLarge { max: usize },
+    BatchItem { index: usize, error: Box<ValidationError> },
+    InvalidIdempotencyKey,
+    InvalidValueRange,
+    InvalidSortBy,
+    InvalidSortDir,
+    InvalidItemId,
+    InvalidCursor,
+    CursorNotAllowed,
+    TenantMissing,
+    InvalidTenant,
+    ConfirmRequired,
+    InvalidTimestamp { param: &'static str },
+    InvalidTimeRange,
+    MetadataNotObject,
+    MetadataTooLarge { max: usize },
+    InvalidActor,
+    InvalidLogDirective,
+}
+
+impl ValidationError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationError::NameEmpty => "name_empty",
+            ValidationError::NameTooLong { .. } => "name_too_long",
+            ValidationError::ValueOutOfRange { .. } => "value_out_of_range",
+            ValidationError::InvalidLimit => "invalid_limit",
+            ValidationError::InvalidOffset => "invalid_offset",
+            ValidationError::BatchEmpty => "batch_empty",
+            ValidationError::BatchTooLarge { .. } => "batch_too_large",
+            ValidationError::BatchItem { error, .. } => error.code(),
+            ValidationError::InvalidIdempotencyKey => "invalid_idempotency_key",
+            ValidationError::InvalidValueRange => "invalid_value_range",
+            ValidationError::InvalidSortBy => "invalid_sort_by",
+            ValidationError::InvalidSortDir => "invalid_sort_dir",
+            ValidationError::InvalidItemId => "invalid_item_id",
+            ValidationError::InvalidCursor => "invalid_cursor",
+            ValidationError::CursorNotAllowed => "cursor_not_allowed",
+            ValidationError::TenantMissing => "tenant_missing",
+            ValidationError::InvalidTenant => "invalid_tenant",
+            ValidationError::ConfirmRequired => "confirm_required",
+            ValidationError::InvalidTimestamp { .. } => "invalid_timestamp",
+            ValidationError::InvalidTimeRange => "invalid_time_range",
+            ValidationError::MetadataNotObject => "metadata_not_object",
+            ValidationError::MetadataTooLarge { .. } => "metadata_too_large",
+            ValidationError::InvalidActor => "invalid_actor",
+            ValidationError::InvalidLogDirective => "invalid_log_directive",
+        }
+    }
+
+    // Request body field the error refers to, if it concerns a single field
+    pub fn field(&self) -> Option<&'static str> {
+        match self {
+            ValidationError::NameEmpty | ValidationError::NameTooLong { .. } => Some("name"),
+            ValidationError::ValueOutOfRange { .. } => Some("value"),
+            ValidationError::ConfirmRequired => Some("confirm"),
+            ValidationError::InvalidLogDirective => Some("directive"),
+            ValidationError::MetadataNotObject | ValidationError::MetadataTooLarge { .. } => Some("metadata"),
+            _ => None,
+        }
+    }
 }
 
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::NameEmpty => write!(f, "name cannot be empty"),
+            ValidationError::NameTooLong { max } => write!(f, "name cannot exceed {} characters", max),
+            ValidationError::ValueOutOfRange { min, max } => {
+                write!(f, "value must be between {} and {}", min, max)
+            }
+            ValidationError::InvalidLimit => write!(f, "limit must be greater than 0"),
+            ValidationError::InvalidOffset => write!(f, "offset cannot be negative"),
+            ValidationError::BatchEmpty => write!(f, "batch cannot be empty"),
+            ValidationError::BatchTooLarge { max } => {
+                write!(f, "batch cannot contain more than {} items", max)
+            }
+            ValidationError::BatchItem { index, error } => write!(f, "item {}: {}", index, error),
+            ValidationError::InvalidIdempotencyKey => {
+                write!(f, "Idempotency-Key must be between 1 and 255 visible ASCII characters")
+            }
+            ValidationError::InvalidValueRange => {
+                write!(f, "min_value cannot be greater than max_value")
+            }
+            ValidationError::InvalidSortBy => {
+                write!(f, "sort_by must be one of created_at, name, value")
+            }
+            ValidationError::InvalidSortDir => write!(f, "sort_dir must be asc or desc"),
+            ValidationError::InvalidItemId => write!(f, "id must be a UUID"),
+            ValidationError::InvalidCursor => write!(f, "cursor is malformed"),
+            ValidationError::CursorNotAllowed => {
+                write!(f, "cursor cannot be combined with offset or a sort other than created_at desc")
+            }
+            ValidationError::TenantMissing => write!(f, "X-Tenant-Id header is required"),
+            ValidationError::InvalidTenant => {
+                write!(f, "X-Tenant-Id must be 1 to 64 ASCII letters, digits, '-' or '_'")
+            }
+            ValidationError::ConfirmRequired => write!(f, "confirm must be true to delete by filter"),
+            ValidationError::InvalidTimestamp { param } => {
+                write!(f, "{} must be an RFC3339 timestamp", param)
+            }
+            ValidationError::InvalidTimeRange => write!(f, "from cannot be later than to"),
+            ValidationError::MetadataNotObject => write!(f, "metadata must be a JSON object"),
+            ValidationError::MetadataTooLarge { max } => {
+                write!(f, "metadata cannot exceed {} bytes when serialized", max)
+            }
+            ValidationError::InvalidActor => {
+                write!(f, "X-Actor must be between 1 and 128 visible ASCII characters")
+            }
+            ValidationError::InvalidLogDirective => write!(f, "directive must be a valid RUST_LOG filter"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
 impl Item {
-    pub fn validate_name(name: &str) -> Result<(), String> {
+    // max_len is the configured NAME_MAX_LEN, counted in bytes
+    pub fn validate_name(name: &str, max_len: usize) -> Result<(), ValidationError> {
         if name.trim().is_empty() {
-            return Err("name cannot be empty".to_string());
+            return Err(ValidationError::NameEmpty);
+        }
+        if name.len() > max_len {
+            return Err(ValidationError::NameTooLong { max: max_len });
+        }
+        Ok(())
+    }
+
+    // Only objects are accepted so metadata can always be extended with more keys
+    pub fn validate_metadata(metadata: &serde_json::Value, max_bytes: usize) -> Result<(), ValidationError> {
+        if !metadata.is_object() {
+            return Err(ValidationError::MetadataNotObject);
         }
-        if name.len() > 255 {
-            return Err("name cannot exceed 255 characters".to_string());
+        if metadata.to_string().len() > max_bytes {
+            return Err(ValidationError::MetadataTooLarge { max: max_bytes });
         }
         Ok(())
     }
+
+    pub fn validate_value(value: i64, min: i64, max: i64) -> Result<(), ValidationError> {
+        if value < min || value > max {
+            return Err(ValidationError::ValueOutOfRange { min, max });
+        }
+        Ok(())
+    }
+
+    // Strong ETag for the item's current version. updated_at changes on every write;
+    // name and value are hashed too so two writes within one clock tick still differ.
+    // DefaultHasher is only stable within a build, which is enough for a cache
+    // validator: a deploy at worst turns one conditional GET into a full response.
+    pub fn etag(&self) -> String {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::hash::DefaultHasher::new();
+        (&self.id, &self.name, self.value, self.updated_at).hash(&mut hasher);
+        format!("\"{:016x}\"", hasher.finish())
+    }
+}
+
+// Whether an If-None-Match header value matches the ETag. The comparison is weak,
+// as RFC 9110 requires for If-None-Match, so W/ prefixes are ignored.
+pub fn if_none_match(header: &str, etag: &str) -> bool {
+    header.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.trim_start_matches("W/") == etag.trim_start_matches("W/")
+    })
+}
+
+// Render items as CSV with a header row (id,name,value,created_at)
+pub fn items_to_csv(items: &[Item]) -> String {
+    let mut csv = String::from("id,name,value,created_at\r\n");
+    for item in items {
+        csv.push_str(&format!(
+            "{},{},{},{}\r\n",
+            item.id,
+            csv_field(&item.name),
+            item.value,
+            item.created_at.to_rfc3339()
+        ));
+    }
+    csv
+}
+
+// Quote a field per RFC 4180 when it contains a delimiter, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Validate a client-supplied Idempotency-Key header value
+pub fn validate_idempotency_key(key: &str) -> Result<(), ValidationError> {
+    if key.is_empty() || key.len() > 255 || !key.bytes().all(|b| b.is_ascii_graphic()) {
+        return Err(ValidationError::InvalidIdempotencyKey);
+    }
+    Ok(())
+}
+
+// The X-Actor header names who made a change, for the audit log. It is set by the
+// gateway that authenticated the caller; spaces are allowed for display names.
+pub fn validate_actor(actor: &str) -> Result<(), ValidationError> {
+    let visible = actor.bytes().all(|b| b.is_ascii_graphic() || b == b' ');
+    if actor.trim().is_empty() || actor.len() > 128 || !visible {
+        return Err(ValidationError::InvalidActor);
+    }
+    Ok(())
+}
+
+// Validate a tenant id from the X-Tenant-Id header. The charset keeps ids safe to use
+// in Kafka headers, metric labels and log lines without escaping.
+pub fn validate_tenant_id(tenant_id: &str) -> Result<(), ValidationError> {
+    if tenant_id.is_empty()
+        || tenant_id.len() > 64
+        || !tenant_id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    {
+        return Err(ValidationError::InvalidTenant);
+    }
+    Ok(())
+}
+
+// Parse an item id, returning it in the canonical lowercase hyphenated form that
+// the database renders with id::text
+pub fn parse_item_id(id: &str) -> Result<String, ValidationError> {
+    uuid::Uuid::parse_str(id)
+        .map(|id| id.to_string())
+        .map_err(|_| ValidationError::InvalidItemId)
+}
+
+impl LookupItemsRequest {
+    // Canonical ids to fetch, reporting the index of the first malformed one
+    pub fn parse_ids(&self) -> Result<Vec<String>, ValidationError> {
+        if self.ids.is_empty() {
+            return Err(ValidationError::BatchEmpty);
+        }
+        if self.ids.len() > LOOKUP_MAX_IDS {
+            return Err(ValidationError::BatchTooLarge { max: LOOKUP_MAX_IDS });
+        }
+        self.ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| {
+                parse_item_id(id).map_err(|e| ValidationError::BatchItem { index, error: Box::new(e) })
+            })
+            .collect()
+    }
+}
+
+impl DeleteByFilterRequest {
+    // The filters as list parameters, so deletes match exactly what a list would return
+    pub fn filters(&self) -> Result<ListItemsParams, ValidationError> {
+        if !self.confirm {
+            return Err(ValidationError::ConfirmRequired);
+        }
+        if let (Some(min), Some(max)) = (self.min_value, self.max_value)
+            && min > max
+        {
+            return Err(ValidationError::InvalidValueRange);
+        }
+        Ok(ListItemsParams {
+            name_contains: self.name_contains.clone(),
+            min_value: self.min_value,
+            max_value: self.max_value,
+            ..Default::default()
+        })
+    }
+}
+
+impl ItemEvent {
+    pub fn item_id(&self) -> &str {
+        match self {
+            ItemEvent::Created { id, .. } => id,
+            ItemEvent::Updated { id, .. } => id,
+            ItemEvent::Deleted { id, .. } => id,
+        }
+    }
+
+    pub fn tenant_id(&self) -> &str {
+        match self {
+            ItemEvent::Created { tenant_id, .. } => tenant_id,
+            ItemEvent::Updated { tenant_id, .. } => tenant_id,
+            ItemEvent::Deleted { tenant_id, .. } => tenant_id,
+        }
+    }
+}
+
+impl CreateItemRequest {
+    // Check every field and report all problems at once, in field order
+    pub fn validate(&self, cfg: &Config) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        if let Err(e) = Item::validate_name(&self.name, cfg.name_max_len) {
+            errors.push(e);
+        }
+        if let Some(value) = self.value
+            && let Err(e) = Item::validate_value(value, cfg.value_min, cfg.value_max)
+        {
+            errors.push(e);
+        }
+        if let Some(metadata) = &self.metadata
+            && let Err(e) = Item::validate_metadata(metadata, cfg.metadata_max_bytes)
+        {
+            errors.push(e);
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    // Validate a batch with the same checks as a single create, reporting every
+    // problem wrapped with the index of the entry it belongs to
+    pub fn validate_batch(items: &[CreateItemRequest], cfg: &Config) -> Result<(), Vec<ValidationError>> {
+        if items.is_empty() {
+            return Err(vec![ValidationError::BatchEmpty]);
+        }
+        if items.len() > cfg.batch_max_items {
+            return Err(vec![ValidationError::BatchTooLarge { max: cfg.batch_max_items }]);
+        }
+        let errors: Vec<ValidationError> = items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| item.validate(cfg).err().map(|errors| (index, errors)))
+            .flat_map(|(index, errors)| {
+                errors
+                    .into_iter()
+                    .map(move |error| ValidationError::BatchItem { index, error: Box::new(error) })
+            })
+            .collect();
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+impl ListItemsParams {
+    // Returns (limit, offset) with default_limit applied and limit clamped to max_limit,
+    // after checking that the filters are consistent
+    pub fn resolve(&self, default_limit: i64, max_limit: i64) -> Result<(i64, i64), ValidationError> {
+        let limit = self.limit.unwrap_or(default_limit);
+        if limit <= 0 {
+            return Err(ValidationError::InvalidLimit);
+        }
+        let offset = self.offset.unwrap_or(0);
+        if offset < 0 {
+            return Err(ValidationError::InvalidOffset);
+        }
+        if let (Some(min), Some(max)) = (self.min_value, self.max_value)
+            && min > max
+        {
+            return Err(ValidationError::InvalidValueRange);
+        }
+        self.sort()?;
+        self.after()?;
+        self.created_range()?;
+        Ok((limit.min(max_limit), offset))
+    }
+
+    // Parsed from/to bounds; an empty range (from == to) is allowed and matches nothing
+    pub fn created_range(&self) -> Result<CreatedRange, ValidationError> {
+        let parse = |raw: Option<&str>, param| {
+            raw.map(|raw| {
+                DateTime::parse_from_rfc3339(raw)
+                    .map(|timestamp| timestamp.with_timezone(&Utc))
+                    .map_err(|_| ValidationError::InvalidTimestamp { param })
+            })
+            .transpose()
+        };
+        let range = CreatedRange {
+            from: parse(self.from.as_deref(), "from")?,
+            to: parse(self.to.as_deref(), "to")?,
+        };
+        if let (Some(from), Some(to)) = (range.from, range.to)
+            && from > to
+        {
+            return Err(ValidationError::InvalidTimeRange);
+        }
+        Ok(range)
+    }
+
+    // Keyset cursors only follow the default newest-first order
+    pub fn is_keyset_order(&self) -> bool {
+        matches!(self.sort(), Ok(("created_at", "DESC")))
+    }
+
+    // Decoded cursor, if one was given; it replaces offset and needs the default order
+    pub fn after(&self) -> Result<Option<ItemCursor>, ValidationError> {
+        let Some(raw) = self.cursor.as_deref() else {
+            return Ok(None);
+        };
+        if self.offset.is_some() || !self.is_keyset_order() {
+            return Err(ValidationError::CursorNotAllowed);
+        }
+        ItemCursor::decode(raw).map(Some)
+    }
+
+    // Returns the (column, direction) to order by, defaulting to newest first.
+    // Both come from an allowlist so they are safe to splice into SQL.
+    pub fn sort(&self) -> Result<(&'static str, &'static str), ValidationError> {
+        let column = match self.sort_by.as_deref() {
+            None | Some("created_at") => "created_at",
+            Some("name") => "name",
+            Some("value") => "value",
+            Some(_) => return Err(ValidationError::InvalidSortBy),
+        };
+        let direction = match self.sort_dir.as_deref() {
+            None | Some("desc") => "DESC",
+            Some("asc") => "ASC",
+            Some(_) => return Err(ValidationError::InvalidSortDir),
+        };
+        Ok((column, direction))
+    }
+
+    // ILIKE pattern for name_contains, with LIKE wildcards in the input escaped so
+    // they match literally
+    pub fn name_pattern(&self) -> Option<String> {
+        let needle = self.name_contains.as_deref()?;
+        let mut pattern = String::with_capacity(needle.len() + 2);
+        pattern.push('%');
+        for c in needle.chars() {
+            if matches!(c, '%' | '_' | '\\') {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        }
+        pattern.push('%');
+        Some(pattern)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_lookup_ids_are_validated_and_canonical() {
+        let request = LookupItemsRequest {
+            ids: vec!["67E55044-10B1-426F-9247-BB680E5FE0C8".to_string()],
+        };
+        assert_eq!(request.parse_ids().unwrap(), vec!["67e55044-10b1-426f-9247-bb680e5fe0c8"]);
+
+        let request = LookupItemsRequest {
+            ids: vec!["67e55044-10b1-426f-9247-bb680e5fe0c8".to_string(), "nope".to_string()],
+        };
+        let err = request.parse_ids().unwrap_err();
+        assert_eq!(err.code(), "invalid_item_id");
+        assert_eq!(err.to_string(), "item 1: id must be a UUID");
+
+        let empty = LookupItemsRequest { ids: Vec::new() };
+        assert_eq!(empty.parse_ids(), Err(ValidationError::BatchEmpty));
+
+        let too_many = LookupItemsRequest {
+            ids: vec!["67e55044-10b1-426f-9247-bb680e5fe0c8".to_string(); LOOKUP_MAX_IDS + 1],
+        };
+        assert_eq!(too_many.parse_ids(), Err(ValidationError::BatchTooLarge { max: LOOKUP_MAX_IDS }));
+    }
+
+    #[test]
+    fn test_create_request_value_is_lenient() {
+        let value = |json: &str| {
+            serde_json::from_str::<CreateItemRequest>(&format!(r#"{{"name": "x", "value": {}}}"#, json)).map(|r| r.value)
+        };
+        assert_eq!(value("42").unwrap(), Some(42));
+        assert_eq!(value(r#""42""#).unwrap(), Some(42));
+        assert_eq!(value(r#""-7""#).unwrap(), Some(-7));
+        assert_eq!(value("42.0").unwrap(), Some(42));
+        assert_eq!(value("null").unwrap(), None);
+        assert_eq!(serde_json::from_str::<CreateItemRequest>(r#"{"name": "x"}"#).unwrap().value, None);
+
+        for rejected in ["42.5", r#""abc""#, r#""42.0""#, "9223372036854775808", "1e19", "true"] {
+            assert!(value(rejected).is_err(), "{} should be rejected", rejected);
+        }
+    }
+
+    #[test]
+    fn test_etag_tracks_item_version() {
+        let item = Item {
+            id: "67e55044-10b1-426f-9247-bb680e5fe0c8".to_string(),
+            tenant_id: DEFAULT_TENANT.to_string(),
+            name: "Widget".to_string(),
+            value: 5,
+            created_at: "2024-01-01T00:00:00Z".parse().unwrap(),
+            updated_at: "2024-01-01T00:00:00Z".parse().unwrap(),
+            metadata: None,
+        };
+        let etag = item.etag();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert_eq!(item.clone().etag(), etag);
+
+        let renamed = Item { name: "Gadget".to_string(), ..item.clone() };
+        let revalued = Item { value: 6, ..item.clone() };
+        let touched = Item { updated_at: "2024-01-02T00:00:00Z".parse().unwrap(), ..item.clone() };
+        for changed in [renamed, revalued, touched] {
+            assert_ne!(changed.etag(), etag);
+        }
+
+        assert!(if_none_match(&etag, &etag));
+        assert!(if_none_match(&format!("\"other\", W/{}", etag), &etag));
+        assert!(if_none_match("*", &etag));
+        assert!(!if_none_match("\"other\"", &etag));
+    }
+
+    #[test]
+    fn test_delete_by_filter_requires_confirm() {
+        let request: DeleteByFilterRequest = serde_json::from_str(r#"{"name_contains":"old"}"#).unwrap();
+        assert_eq!(request.filters().unwrap_err(), ValidationError::ConfirmRequired);
+
+        let request: DeleteByFilterRequest =
+            serde_json::from_str(r#"{"min_value":5,"max_value":1,"confirm":true}"#).unwrap();
+        assert_eq!(request.filters().unwrap_err(), ValidationError::InvalidValueRange);
+
+        let request: DeleteByFilterRequest =
+            serde_json::from_str(r#"{"name_contains":"old","max_value":10,"confirm":true}"#).unwrap();
+        let filters = request.filters().unwrap();
+        assert_eq!(filters.name_contains.as_deref(), Some("old"));
+        assert_eq!(filters.min_value, None);
+        assert_eq!(filters.max_value, Some(10));
+    }
+
+    #[test]
+    fn test_items_to_csv_quotes_special_characters() {
+        let created_at: DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+        let item = |id: &str, name: &str| Item {
+            id: id.to_string(),
+            tenant_id: DEFAULT_TENANT.to_string(),
+            name: name.to_string(),
+            value: 7,
+            created_at,
+            updated_at: created_at,
+            metadata: None,
+        };
+
+        let csv = items_to_csv(&[item("1", "plain"), item("2", "a, \"quoted\"\nname")]);
+        assert_eq!(
+            csv,
+            "id,name,value,created_at\r\n\
+             1,plain,7,2024-01-01T00:00:00+00:00\r\n\
+             2,\"a, \"\"quoted\"\"\nname\",7,2024-01-01T00:00:00+00:00\r\n"
+        );
+        assert_eq!(items_to_csv(&[]), "id,name,value,created_at\r\n");
+    }
+
     #[test]
     fn test_validate_name_empty() {
-        let result = Item::validate_name("");
+        let result = Item::validate_name("", 255);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "name cannot be empty");
+        assert_eq!(result.unwrap_err().to_string(), "name cannot be empty");
     }
 
     #[test]
     fn test_validate_name_whitespace() {
-        let result = Item::validate_name("   ");
+        let result = Item::validate_name("   ", 255);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_validate_name_too_long() {
         let long_name = "a".repeat(256);
-        let result = Item::validate_name(&long_name);
+        let result = Item::validate_name(&long_name, 255);
         assert!(result.is_err());
+        assert_eq!(result.unwrap_err().code(), "name_too_long");
+
+        // The limit is configurable
+        assert!(Item::validate_name("abcd", 4).is_ok());
+        assert_eq!(
+            Item::validate_name("abcde", 4).unwrap_err().to_string(),
+            "name cannot exceed 4 characters"
+        );
     }
 
     #[test]
     fn test_validate_name_valid() {
-        let result = Item::validate_name("valid name");
+        let result = Item::validate_name("valid name", 255);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_validate_value_range() {
+        assert!(Item::validate_value(0, 0, 100).is_ok());
+        assert!(Item::validate_value(100, 0, 100).is_ok());
+        assert_eq!(
+            Item::validate_value(101, 0, 100),
+            Err(ValidationError::ValueOutOfRange { min: 0, max: 100 })
+        );
+        assert!(Item::validate_value(-1, 0, 100).is_err());
+    }
+
+    fn batch_item(name: &str, value: Option<i64>) -> CreateItemRequest {
+        CreateItemRequest { name: name.to_string(), value, metadata: None }
+    }
+
+    #[test]
+    fn test_validate_metadata() {
+        let nested = serde_json::json!({"tags": ["a", "b"], "owner": {"team": "core", "oncall": null}});
+        assert!(Item::validate_metadata(&nested, 1024).is_ok());
+        assert!(Item::validate_metadata(&serde_json::json!({}), 2).is_ok());
+
+        for scalar in [serde_json::json!(1), serde_json::json!("x"), serde_json::json!([1]), serde_json::Value::Null] {
+            assert_eq!(Item::validate_metadata(&scalar, 1024), Err(ValidationError::MetadataNotObject));
+        }
+
+        let err = Item::validate_metadata(&nested, 16).unwrap_err();
+        assert_eq!(err, ValidationError::MetadataTooLarge { max: 16 });
+        assert_eq!(err.field(), Some("metadata"));
+    }
+
+    #[test]
+    fn test_validate_request_reports_every_field() {
+        let cfg = Config::from_env().unwrap();
+
+        assert!(batch_item("a", Some(cfg.value_min)).validate(&cfg).is_ok());
+        assert!(batch_item("a", None).validate(&cfg).is_ok());
+
+        let errors = batch_item(" ", Some(cfg.value_max + 1)).validate(&cfg).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError::NameEmpty,
+                ValidationError::ValueOutOfRange { min: cfg.value_min, max: cfg.value_max },
+            ]
+        );
+        assert_eq!(errors[0].field(), Some("name"));
+        assert_eq!(errors[1].field(), Some("value"));
+    }
+
+    #[test]
+    fn test_validate_batch_valid() {
+        let cfg = Config::from_env().unwrap();
+        let items = vec![batch_item("a", Some(1)), batch_item("b", None)];
+        assert!(CreateItemRequest::validate_batch(&items, &cfg).is_ok());
+    }
+
+    #[test]
+    fn test_validate_batch_reports_every_invalid_entry() {
+        let cfg = Config::from_env().unwrap();
+        let items = vec![
+            batch_item("a", Some(1)),
+            batch_item("", None),
+            batch_item(" ", Some(cfg.value_max + 1)),
+        ];
+        let errors = CreateItemRequest::validate_batch(&items, &cfg).unwrap_err();
+        let out_of_range = ValidationError::ValueOutOfRange { min: cfg.value_min, max: cfg.value_max };
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError::BatchItem { index: 1, error: Box::new(ValidationError::NameEmpty) },
+                ValidationError::BatchItem { index: 2, error: Box::new(ValidationError::NameEmpty) },
+                ValidationError::BatchItem { index: 2, error: Box::new(out_of_range) },
+            ]
+        );
+        assert_eq!(errors[0].code(), "name_empty");
+        assert_eq!(errors[0].to_string(), "item 1: name cannot be empty");
+    }
+
+    #[test]
+    fn test_validate_batch_size() {
+        let cfg = Config { batch_max_items: 1, ..Config::from_env().unwrap() };
+        assert_eq!(CreateItemRequest::validate_batch(&[], &cfg), Err(vec![ValidationError::BatchEmpty]));
+
+        let items = vec![batch_item("a", None), batch_item("b", None)];
+        assert_eq!(
+            CreateItemRequest::validate_batch(&items, &cfg),
+            Err(vec![ValidationError::BatchTooLarge { max: 1 }])
+        );
+    }
+
+    #[test]
+    fn test_validate_idempotency_key() {
+        assert!(validate_idempotency_key("3f2c1b7e-retry-1").is_ok());
+        assert_eq!(validate_idempotency_key(""), Err(ValidationError::InvalidIdempotencyKey));
+        assert!(validate_idempotency_key("has space").is_err());
+        assert!(validate_idempotency_key(&"k".repeat(256)).is_err());
+    }
+
+    #[test]
+    fn test_validate_actor() {
+        assert!(validate_actor("alice@example.com").is_ok());
+        assert!(validate_actor("Jane Doe").is_ok());
+        assert_eq!(validate_actor(" "), Err(ValidationError::InvalidActor));
+        assert!(validate_actor(&"a".repeat(129)).is_err());
+        assert!(validate_actor("tab\there").is_err());
+    }
+
+    #[test]
+    fn test_audit_action_round_trip() {
+        for action in [AuditAction::Created, AuditAction::Updated, AuditAction::Deleted] {
+            assert_eq!(action.as_str().parse::<AuditAction>(), Ok(action));
+            assert_eq!(serde_json::to_value(action).unwrap(), action.as_str());
+        }
+        assert!("renamed".parse::<AuditAction>().is_err());
+    }
+
+    #[test]
+    fn test_validate_tenant_id() {
+        assert!(validate_tenant_id("acme-corp_2").is_ok());
+        assert_eq!(validate_tenant_id(""), Err(ValidationError::InvalidTenant));
+        assert!(validate_tenant_id("acme corp").is_err());
+        assert!(validate_tenant_id("acme/corp").is_err());
+        assert!(validate_tenant_id(&"t".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_list_params_defaults() {
+        let params = ListItemsParams::default();
+        assert_eq!(params.resolve(20, 100), Ok((20, 0)));
+    }
+
+    #[test]
+    fn test_list_params_limit_capped() {
+        let params = ListItemsParams { limit: Some(500), offset: Some(10), ..Default::default() };
+        assert_eq!(params.resolve(20, 100), Ok((100, 10)));
+    }
+
+    #[test]
+    fn test_list_params_invalid() {
+        let params = ListItemsParams { limit: Some(0), ..Default::default() };
+        assert_eq!(params.resolve(20, 100), Err(ValidationError::InvalidLimit));
+
+        let params = ListItemsParams { limit: Some(-5), ..Default::default() };
+        assert!(params.resolve(20, 100).is_err());
+
+        let params = ListItemsParams { offset: Some(-1), ..Default::default() };
+        assert!(params.resolve(20, 100).is_err());
+    }
+
+    #[test]
+    fn test_list_params_value_range() {
+        let params = ListItemsParams { min_value: Some(10), max_value: Some(5), ..Default::default() };
+        assert_eq!(params.resolve(20, 100), Err(ValidationError::InvalidValueRange));
+
+        let params = ListItemsParams { min_value: Some(5), max_value: Some(5), ..Default::default() };
+        assert!(params.resolve(20, 100).is_ok());
+    }
+
+    #[test]
+    fn test_list_params_created_range() {
+        let params = ListItemsParams {
+            from: Some("2024-01-01T01:00:00+01:00".to_string()),
+            to: Some("2024-01-02T00:00:00Z".to_string()),
+            ..Default::default()
+        };
+        let range = params.created_range().unwrap();
+        assert_eq!(range.from, Some("2024-01-01T00:00:00Z".parse().unwrap()));
+        // Inclusive start, exclusive end
+        assert!(range.contains("2024-01-01T00:00:00Z".parse().unwrap()));
+        assert!(!range.contains("2024-01-02T00:00:00Z".parse().unwrap()));
+        assert!(!range.contains("2023-12-31T23:59:59Z".parse().unwrap()));
+
+        let params = ListItemsParams { to: Some("2024-01-02".to_string()), ..Default::default() };
+        assert_eq!(params.resolve(20, 100), Err(ValidationError::InvalidTimestamp { param: "to" }));
+        assert_eq!(params.resolve(20, 100).unwrap_err().to_string(), "to must be an RFC3339 timestamp");
+
+        let params = ListItemsParams {
+            from: Some("2024-01-02T00:00:00Z".to_string()),
+            to: Some("2024-01-01T00:00:00Z".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(params.resolve(20, 100), Err(ValidationError::InvalidTimeRange));
+    }
+
+    #[test]
+    fn test_list_params_sort() {
+        assert_eq!(ListItemsParams::default().sort(), Ok(("created_at", "DESC")));
+
+        let params = ListItemsParams {
+            sort_by: Some("value".to_string()),
+            sort_dir: Some("asc".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(params.sort(), Ok(("value", "ASC")));
+
+        let params = ListItemsParams { sort_by: Some("id; DROP TABLE items".to_string()), ..Default::default() };
+        assert_eq!(params.resolve(20, 100), Err(ValidationError::InvalidSortBy));
+
+        let params = ListItemsParams { sort_dir: Some("up".to_string()), ..Default::default() };
+        assert_eq!(params.resolve(20, 100), Err(ValidationError::InvalidSortDir));
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = ItemCursor {
+            created_at: "2024-01-01T00:00:00.123456789Z".parse().unwrap(),
+            id: "6f1c2b7e-3d4a-4b5c-8d9e-0f1a2b3c4d5e".to_string(),
+        };
+        assert_eq!(ItemCursor::decode(&cursor.encode()), Ok(cursor));
+    }
+
+    #[test]
+    fn test_cursor_rejects_malformed_input() {
+        use base64::Engine;
+        let encode = |raw: &str| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw);
+
+        assert_eq!(ItemCursor::decode("not base64!"), Err(ValidationError::InvalidCursor));
+        assert_eq!(ItemCursor::decode(&encode("no separator")), Err(ValidationError::InvalidCursor));
+        assert_eq!(
+            ItemCursor::decode(&encode("yesterday|6f1c2b7e-3d4a-4b5c-8d9e-0f1a2b3c4d5e")),
+            Err(ValidationError::InvalidCursor)
+        );
+        assert_eq!(ItemCursor::decode(&encode("2024-01-01T00:00:00Z|42")), Err(ValidationError::InvalidCursor));
+    }
+
+    #[test]
+    fn test_list_params_cursor_needs_default_order() {
+        let cursor = ItemCursor {
+            created_at: Utc::now(),
+            id: "6f1c2b7e-3d4a-4b5c-8d9e-0f1a2b3c4d5e".to_string(),
+        }
+        .encode();
+
+        let params = ListItemsParams { cursor: Some(cursor.clone()), ..Default::default() };
+        assert!(params.resolve(20, 100).is_ok());
+
+        let params = ListItemsParams { cursor: Some(cursor.clone()), offset: Some(0), ..Default::default() };
+        assert_eq!(params.resolve(20, 100), Err(ValidationError::CursorNotAllowed));
+
+        let params = ListItemsParams { cursor: Some(cursor), sort_by: Some("name".to_string()), ..Default::default() };
+        assert_eq!(params.resolve(20, 100), Err(ValidationError::CursorNotAllowed));
+    }
+
+    #[test]
+    fn test_name_pattern_escapes_wildcards() {
+        let params = ListItemsParams { name_contains: Some("50%_off\\".to_string()), ..Default::default() };
+        assert_eq!(params.name_pattern().as_deref(), Some("%50\\%\\_off\\\\%"));
+
+        assert_eq!(ListItemsParams::default().name_pattern(), None);
+    }
+
+    #[test]
+    fn test_name_pattern_keeps_sql_literal() {
+        let params = ListItemsParams { name_contains: Some("'; DROP TABLE items; --".to_string()), ..Default::default() };
+        assert_eq!(params.name_pattern().as_deref(), Some("%'; DROP TABLE items; --%"));
+    }
 }
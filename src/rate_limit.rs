@@ -0,0 +1,113 @@
+use dashmap::DashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+// Token-bucket rate limiter keyed by client IP
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate_per_sec: f64,
+    burst: f64,
+    buckets: DashMap<IpAddr, Bucket>,
+}
+
+impl RateLimiter {
+    pub fn new(rate_per_sec: f64, burst: u32) -> Self {
+        RateLimiter {
+            rate_per_sec,
+            burst: f64::from(burst.max(1)),
+            buckets: DashMap::new(),
+        }
+    }
+
+    // Take one token for the client, or return how long until one is available
+    pub fn check(&self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        let mut bucket = self.buckets.entry(client).or_insert(Bucket {
+            tokens: self.burst,
+            last_refill: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate_per_sec).min(self.burst);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate_per_sec))
+        }
+    }
+
+    // Drop buckets idle long enough to have refilled completely; they are
+    // indistinguishable from a fresh bucket so removing them loses nothing
+    pub fn prune(&self, now: Instant) {
+        if self.rate_per_sec <= 0.0 {
+            self.buckets.clear();
+            return;
+        }
+
+        let refill_time = Duration::from_secs_f64(self.burst / self.rate_per_sec);
+        self.buckets
+            .retain(|_, bucket| now.saturating_duration_since(bucket.last_refill) < refill_time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client() -> IpAddr {
+        "10.0.0.1".parse().unwrap()
+    }
+
+    #[test]
+    fn test_allows_burst_then_limits() {
+        let limiter = RateLimiter::new(1.0, 3);
+        let now = Instant::now();
+
+        assert!(limiter.check(client(), now).is_ok());
+        assert!(limiter.check(client(), now).is_ok());
+        assert!(limiter.check(client(), now).is_ok());
+
+        let retry_after = limiter.check(client(), now).unwrap_err();
+        assert!(retry_after <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_refills_over_time() {
+        let limiter = RateLimiter::new(2.0, 1);
+        let now = Instant::now();
+
+        assert!(limiter.check(client(), now).is_ok());
+        assert!(limiter.check(client(), now).is_err());
+        assert!(limiter.check(client(), now + Duration::from_millis(500)).is_ok());
+    }
+
+    #[test]
+    fn test_clients_are_independent() {
+        let limiter = RateLimiter::new(1.0, 1);
+        let now = Instant::now();
+
+        assert!(limiter.check(client(), now).is_ok());
+        assert!(limiter.check("10.0.0.2".parse().unwrap(), now).is_ok());
+    }
+
+    #[test]
+    fn test_prune_removes_idle_buckets() {
+        let limiter = RateLimiter::new(1.0, 2);
+        let now = Instant::now();
+
+        limiter.check(client(), now).unwrap();
+        limiter.prune(now + Duration::from_secs(1));
+        assert_eq!(limiter.buckets.len(), 1);
+
+        limiter.prune(now + Duration::from_secs(3));
+        assert!(limiter.buckets.is_empty());
+    }
+}
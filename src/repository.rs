@@ -0,0 +1,1387 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::AppError;
+use crate::models::{
+    AuditAction, AuditEntry, CreatedRange, Item, ItemCursor, ItemEvent, ListItemsParams, ValidationError, W3CTraceContext,
+    ANONYMOUS_ACTOR,
+};
+
+// Unique index on (tenant_id, name), present only while ENFORCE_UNIQUE_NAMES is on
+pub const UNIQUE_NAME_INDEX: &str = "items_name_unique";
+
+// Columns the item queries read, with their information_schema data_type
+const ITEMS_COLUMNS: &[(&str, &str)] = &[
+    ("id", "uuid"),
+    ("tenant_id", "text"),
+    ("name", "text"),
+    ("value", "bigint"),
+    ("created_at", "timestamp with time zone"),
+    ("updated_at", "timestamp with time zone"),
+    ("metadata", "jsonb"),
+];
+
+// Differences between the items table's actual (column, data_type) pairs and
+// ITEMS_COLUMNS. Extra columns are fine; missing or retyped ones break queries.
+fn items_schema_mismatches(actual: &[(String, String)]) -> Vec<String> {
+    ITEMS_COLUMNS
+        .iter()
+        .filter_map(|(column, expected)| match actual.iter().find(|(name, _)| name == column) {
+            None => Some(format!("column {column} is missing")),
+            Some((_, data_type)) if data_type != expected => {
+                Some(format!("column {column} is {data_type}, expected {expected}"))
+            }
+            Some(_) => None,
+        })
+        .collect()
+}
+
+// Request metadata carried into the event and audit entry recorded alongside a write
+#[derive(Debug, Clone, Default)]
+pub struct EventContext {
+    pub request_id: Option<String>,
+    pub trace_context: Option<W3CTraceContext>,
+    // Who made the change, from X-Actor; audited as ANONYMOUS_ACTOR when absent
+    pub actor: Option<String>,
+}
+
+// Result of an insert guarded by an idempotency key
+#[derive(Debug, Clone)]
+pub enum CreateOutcome {
+    Created(Item),
+    // The key was claimed concurrently; this is the item created under it
+    Existing(Item),
+}
+
+// Storage for items. Every write also records the matching ItemEvent and an audit
+// entry atomically with the change, so implementations own the outbox and the audit
+// log as well. Item operations are scoped to one tenant: items of other tenants are
+// invisible, as if absent.
+#[async_trait]
+pub trait Repository: Send + Sync {
+    async fn create_item(
+        &self,
+        tenant_id: &str,
+        name: &str,
+        value: i64,
+        metadata: Option<&serde_json::Value>,
+        idempotency_key: Option<&str>,
+        ctx: &EventContext,
+    ) -> Result<CreateOutcome, AppError>;
+
+    async fn create_items(
+        &self,
+        tenant_id: &str,
+        items: &[(String, i64, Option<serde_json::Value>)],
+        ctx: &EventContext,
+    ) -> Result<Vec<Item>, AppError>;
+
+    // Item created under an idempotency key that has not yet expired
+    async fn find_by_idempotency_key(&self, tenant_id: &str, key: &str) -> Result<Option<Item>, AppError>;
+
+    async fn get_item(&self, tenant_id: &str, id: &str) -> Result<Option<Item>, AppError>;
+
+    // Whether the item exists, without loading its row
+    async fn item_exists(&self, tenant_id: &str, id: &str) -> Result<bool, AppError>;
+
+    // Items matching any of the canonical ids, in no particular order
+    async fn get_items(&self, tenant_id: &str, ids: &[String]) -> Result<Vec<Item>, AppError>;
+
+    // One page of items matching the filters, plus the total number of matches
+    async fn list_items(
+        &self,
+        tenant_id: &str,
+        params: &ListItemsParams,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<Item>, i64), AppError>;
+
+    // Number of items matching the list filters; paging and sort parameters are ignored
+    async fn count_items(&self, tenant_id: &str, params: &ListItemsParams) -> Result<i64, AppError>;
+
+    async fn update_item(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        name: Option<&str>,
+        value: Option<i64>,
+        ctx: &EventContext,
+    ) -> Result<Option<Item>, AppError>;
+
+    // Atomically add delta to the value; the result must stay within [min, max]
+    async fn increment_item(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        delta: i64,
+        (min, max): (i64, i64),
+        ctx: &EventContext,
+    ) -> Result<Option<Item>, AppError>;
+
+    // Queue a fresh created event, marked replayed, for an existing item
+    async fn republish_item(&self, tenant_id: &str, id: &str, ctx: &EventContext) -> Result<Option<Item>, AppError>;
+
+    // Returns false when no item had the id
+    async fn delete_item(&self, tenant_id: &str, id: &str, ctx: &EventContext) -> Result<bool, AppError>;
+
+    // Delete every item matching the list filters, recording one deleted event per
+    // item, and return how many were deleted
+    async fn delete_items_matching(
+        &self,
+        tenant_id: &str,
+        params: &ListItemsParams,
+        ctx: &EventContext,
+    ) -> Result<u64, AppError>;
+
+    // Audit trail of an item, oldest first, including after it was deleted. Empty when
+    // the tenant never had the item.
+    async fn item_history(&self, tenant_id: &str, id: &str) -> Result<Vec<AuditEntry>, AppError>;
+
+    // Whether the items table exists; catches a reachable database whose migrations never ran
+    async fn schema_ok(&self) -> Result<bool, AppError>;
+}
+
+type ItemRow = (String, String, String, i64, DateTime<Utc>, DateTime<Utc>, Option<serde_json::Value>);
+
+fn item_from_row((id, tenant_id, name, value, created_at, updated_at, metadata): ItemRow) -> Item {
+    Item {
+        id,
+        tenant_id,
+        name,
+        value,
+        created_at,
+        updated_at,
+        metadata,
+    }
+}
+
+fn created_event(item: &Item, ctx: &EventContext, replayed: bool) -> ItemEvent {
+    ItemEvent::Created {
+        id: item.id.clone(),
+        tenant_id: item.tenant_id.clone(),
+        name: item.name.clone(),
+        value: item.value,
+        created_at: item.created_at,
+        request_id: ctx.request_id.clone(),
+        replayed,
+        metadata: item.metadata.clone(),
+    }
+}
+
+fn updated_event(item: &Item, ctx: &EventContext) -> ItemEvent {
+    ItemEvent::Updated {
+        id: item.id.clone(),
+        tenant_id: item.tenant_id.clone(),
+        name: item.name.clone(),
+        value: item.value,
+        created_at: item.created_at,
+        request_id: ctx.request_id.clone(),
+    }
+}
+
+fn audit_entry(item_id: &str, action: AuditAction, old: Option<&Item>, new: Option<&Item>, ctx: &EventContext) -> AuditEntry {
+    let snapshot = |item: &Item| serde_json::to_value(item).expect("Item serializes to JSON");
+    AuditEntry {
+        item_id: item_id.to_string(),
+        action,
+        old_value: old.map(snapshot),
+        new_value: new.map(snapshot),
+        actor: ctx.actor.clone().unwrap_or_else(|| ANONYMOUS_ACTOR.to_string()),
+        request_id: ctx.request_id.clone(),
+        timestamp: Utc::now(),
+    }
+}
+
+type AuditRow = (String, String, Option<serde_json::Value>, Option<serde_json::Value>, String, Option<String>, DateTime<Utc>);
+
+fn audit_entry_from_row(
+    (item_id, action, old_value, new_value, actor, request_id, timestamp): AuditRow,
+) -> Result<AuditEntry, AppError> {
+    let action = action
+        .parse()
+        .map_err(|e: String| AppError::Database(sqlx::Error::Decode(e.into())))?;
+    Ok(AuditEntry {
+        item_id,
+        action,
+        old_value,
+        new_value,
+        actor,
+        request_id,
+        timestamp,
+    })
+}
+
+fn deleted_event(tenant_id: &str, id: &str, ctx: &EventContext) -> ItemEvent {
+    ItemEvent::Deleted {
+        id: id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        deleted_at: Utc::now().to_rfc3339(),
+        request_id: ctx.request_id.clone(),
+    }
+}
+
+// Postgres-backed repository; events go to the outbox table in the same transaction
+#[derive(Debug, Clone)]
+pub struct PgRepository {
+    pool: PgPool,
+    idempotency_key_ttl_secs: u64,
+}
+
+impl PgRepository {
+    pub fn new(pool: PgPool, idempotency_key_ttl_secs: u64) -> Self {
+        PgRepository {
+            pool,
+            idempotency_key_ttl_secs,
+        }
+    }
+
+    // Create or drop the unique name index to match the configured flag. This runs at
+    // startup rather than as a migration because migrations cannot depend on config;
+    // creating it fails if duplicate names already exist.
+    pub async fn sync_unique_names(&self, enforce: bool) -> Result<(), sqlx::Error> {
+        let statement = if enforce {
+            format!("CREATE UNIQUE INDEX IF NOT EXISTS {UNIQUE_NAME_INDEX} ON items (tenant_id, name)")
+        } else {
+            format!("DROP INDEX IF EXISTS {UNIQUE_NAME_INDEX}")
+        };
+        sqlx::query(&statement).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    // Compare the live items table with the columns the queries expect. Migrations use
+    // IF NOT EXISTS, so a pre-existing table of a different shape passes them silently
+    // and would otherwise only surface as 500s on the first request.
+    pub async fn check_items_schema(&self) -> Result<Vec<String>, sqlx::Error> {
+        let columns: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT column_name::text, data_type::text
+            FROM information_schema.columns
+            WHERE table_schema = current_schema() AND table_name = 'items'
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(items_schema_mismatches(&columns))
+    }
+}
+
+// Write an event to the outbox within the caller's transaction
+async fn enqueue_outbox_event(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    event: &ItemEvent,
+    ctx: &EventContext,
+) -> Result<(), sqlx::Error> {
+    let trace_context = ctx.trace_context.as_ref();
+    sqlx::query(
+        r#"
+        INSERT INTO outbox (item_id, payload, trace_id, span_id, trace_state, baggage)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(event.item_id())
+    .bind(sqlx::types::Json(event))
+    .bind(trace_context.map(|ctx| ctx.trace_id.as_str()))
+    .bind(trace_context.map(|ctx| ctx.span_id.as_str()))
+    .bind(trace_context.and_then(|ctx| ctx.trace_state.as_deref()))
+    .bind(trace_context.and_then(|ctx| ctx.baggage.as_deref()))
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+// Free the idempotency keys of deleted items within the caller's transaction, so a
+// retried create with such a key makes a new item rather than finding none
+async fn release_idempotency_keys(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    tenant_id: &str,
+    item_ids: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM idempotency_keys WHERE tenant_id = $1 AND item_id = ANY($2::uuid[])")
+        .bind(tenant_id)
+        .bind(item_ids)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+// Write an audit entry within the caller's transaction
+async fn record_audit(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    tenant_id: &str,
+    entry: &AuditEntry,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO item_audit (tenant_id, item_id, action, old_value, new_value, actor, request_id, created_at)
+        VALUES ($1, $2::uuid, $3, $4, $5, $6, $7, $8)
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(&entry.item_id)
+    .bind(entry.action.as_str())
+    .bind(&entry.old_value)
+    .bind(&entry.new_value)
+    .bind(&entry.actor)
+    .bind(&entry.request_id)
+    .bind(entry.timestamp)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+// Current state of an item, locked until the caller's transaction ends so the audit
+// entry's old_value is the state the change actually replaced
+async fn lock_item(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    tenant_id: &str,
+    id: &str,
+) -> Result<Option<Item>, sqlx::Error> {
+    let row = sqlx::query_as::<_, ItemRow>(
+        r#"
+        SELECT id::text, tenant_id, name, value, created_at, updated_at, metadata
+        FROM items
+        WHERE tenant_id = $1 AND id = $2::uuid
+        FOR UPDATE
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(row.map(item_from_row))
+}
+
+// Append the tenant scope and list filters as a WHERE clause; every user-supplied
+// value is bound
+fn push_list_filters<'a>(
+    query: &mut QueryBuilder<'a, Postgres>,
+    tenant_id: &'a str,
+    params: &'a ListItemsParams,
+    name_pattern: Option<&'a str>,
+    created: CreatedRange,
+    after: Option<&ItemCursor>,
+) {
+    query.push(" WHERE tenant_id = ").push_bind(tenant_id);
+    if let Some(pattern) = name_pattern {
+        query.push(" AND name ILIKE ").push_bind(pattern);
+    }
+    if let Some(min) = params.min_value {
+        query.push(" AND value >= ").push_bind(min);
+    }
+    if let Some(max) = params.max_value {
+        query.push(" AND value <= ").push_bind(max);
+    }
+    if let Some(from) = created.from {
+        query.push(" AND created_at >= ").push_bind(from);
+    }
+    if let Some(to) = created.to {
+        query.push(" AND created_at < ").push_bind(to);
+    }
+    // Keyset condition matching ORDER BY created_at DESC, id DESC
+    if let Some(cursor) = after {
+        query
+            .push(" AND (created_at, id) < (")
+            .push_bind(cursor.created_at)
+            .push(", ")
+            .push_bind(cursor.id.clone())
+            .push("::uuid)");
+    }
+}
+
+#[async_trait]
+impl Repository for PgRepository {
+    async fn create_item(
+        &self,
+        tenant_id: &str,
+        name: &str,
+        value: i64,
+        metadata: Option<&serde_json::Value>,
+        idempotency_key: Option<&str>,
+        ctx: &EventContext,
+    ) -> Result<CreateOutcome, AppError> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut tx = self.pool.begin().await?;
+
+        // Claim the idempotency key before inserting, so a concurrent request with the
+        // same key waits here for the winner instead of failing on a unique name. An
+        // expired entry is taken over; a live one means that request won, so return
+        // its item instead.
+        if let Some(key) = idempotency_key {
+            let claimed = sqlx::query_scalar::<_, String>(
+                r#"
+                INSERT INTO idempotency_keys (tenant_id, key, item_id)
+                VALUES ($1, $2, $3::uuid)
+                ON CONFLICT (tenant_id, key) DO UPDATE
+                    SET item_id = EXCLUDED.item_id, created_at = NOW()
+                    WHERE idempotency_keys.created_at <= NOW() - make_interval(secs => $4)
+                RETURNING key
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(key)
+            .bind(&id)
+            .bind(self.idempotency_key_ttl_secs as f64)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if claimed.is_none() {
+                tx.rollback().await?;
+                // Deletes release their items' keys, so a live key without an item is
+                // one whose item was deleted after this request's claim was refused
+                let existing = self
+                    .find_by_idempotency_key(tenant_id, key)
+                    .await?
+                    .ok_or(AppError::IdempotencyConflict)?;
+                return Ok(CreateOutcome::Existing(existing));
+            }
+        }
+
+        let row = sqlx::query_as::<_, ItemRow>(
+            r#"
+            INSERT INTO items (id, tenant_id, name, value, metadata)
+            VALUES ($1::uuid, $2, $3, $4, $5)
+            RETURNING id::text, tenant_id, name, value, created_at, updated_at, metadata
+            "#,
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(name)
+        .bind(value)
+        .bind(metadata)
+        .fetch_one(&mut *tx)
+        .await?;
+        let item = item_from_row(row);
+
+        // Record the event with W3C trace context in the same transaction as the insert
+        enqueue_outbox_event(&mut tx, &created_event(&item, ctx, false), ctx).await?;
+        record_audit(&mut tx, tenant_id, &audit_entry(&item.id, AuditAction::Created, None, Some(&item), ctx)).await?;
+        tx.commit().await?;
+
+        Ok(CreateOutcome::Created(item))
+    }
+
+    async fn create_items(
+        &self,
+        tenant_id: &str,
+        items: &[(String, i64, Option<serde_json::Value>)],
+        ctx: &EventContext,
+    ) -> Result<Vec<Item>, AppError> {
+        let names: Vec<&str> = items.iter().map(|(name, _, _)| name.as_str()).collect();
+        let values: Vec<i64> = items.iter().map(|(_, value, _)| *value).collect();
+        let metadata: Vec<Option<&serde_json::Value>> = items.iter().map(|(_, _, metadata)| metadata.as_ref()).collect();
+
+        let mut tx = self.pool.begin().await?;
+
+        // Single multi-row insert via UNNEST of parallel arrays
+        let rows = sqlx::query_as::<_, ItemRow>(
+            r#"
+            INSERT INTO items (tenant_id, name, value, metadata)
+            SELECT $1, * FROM UNNEST($2::text[], $3::bigint[], $4::jsonb[])
+            RETURNING id::text, tenant_id, name, value, created_at, updated_at, metadata
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&names)
+        .bind(&values)
+        .bind(&metadata)
+        .fetch_all(&mut *tx)
+        .await?;
+        let items: Vec<Item> = rows.into_iter().map(item_from_row).collect();
+
+        // Queue one created event and audit entry per inserted row in the same transaction
+        for item in &items {
+            enqueue_outbox_event(&mut tx, &created_event(item, ctx, false), ctx).await?;
+            record_audit(&mut tx, tenant_id, &audit_entry(&item.id, AuditAction::Created, None, Some(item), ctx)).await?;
+        }
+
+        tx.commit().await?;
+        Ok(items)
+    }
+
+    async fn find_by_idempotency_key(&self, tenant_id: &str, key: &str) -> Result<Option<Item>, AppError> {
+        let row = sqlx::query_as::<_, ItemRow>(
+            r#"
+            SELECT i.id::text, i.tenant_id, i.name, i.value, i.created_at, i.updated_at, i.metadata
+            FROM idempotency_keys k
+            JOIN items i ON i.id = k.item_id
+            WHERE k.tenant_id = $1
+              AND k.key = $2
+              AND k.created_at > NOW() - make_interval(secs => $3)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(key)
+        .bind(self.idempotency_key_ttl_secs as f64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(item_from_row))
+    }
+
+    async fn get_item(&self, tenant_id: &str, id: &str) -> Result<Option<Item>, AppError> {
+        let row = sqlx::query_as::<_, ItemRow>(
+            r#"
+            SELECT id::text, tenant_id, name, value, created_at, updated_at, metadata
+            FROM items
+            WHERE tenant_id = $1 AND id = $2::uuid
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(item_from_row))
+    }
+
+    async fn item_exists(&self, tenant_id: &str, id: &str) -> Result<bool, AppError> {
+        let found = sqlx::query_scalar::<_, i32>("SELECT 1 FROM items WHERE tenant_id = $1 AND id = $2::uuid")
+            .bind(tenant_id)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(found.is_some())
+    }
+
+    async fn get_items(&self, tenant_id: &str, ids: &[String]) -> Result<Vec<Item>, AppError> {
+        // Ids are validated UUIDs, so compare as uuid[] and let the primary key index apply
+        let rows = sqlx::query_as::<_, ItemRow>(
+            r#"
+            SELECT id::text, tenant_id, name, value, created_at, updated_at, metadata
+            FROM items
+            WHERE tenant_id = $1 AND id = ANY($2::uuid[])
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(item_from_row).collect())
+    }
+
+    async fn list_items(
+        &self,
+        tenant_id: &str,
+        params: &ListItemsParams,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<Item>, i64), AppError> {
+        let (sort_column, sort_direction) = params.sort()?;
+        let after = params.after()?;
+        let created = params.created_range()?;
+        let name_pattern = params.name_pattern();
+
+        let mut query = QueryBuilder::new("SELECT id::text, tenant_id, name, value, created_at, updated_at, metadata FROM items");
+        push_list_filters(&mut query, tenant_id, params, name_pattern.as_deref(), created, after.as_ref());
+        // Sort column and direction are allowlisted; id breaks ties so paging is stable
+        query.push(format!(
+            " ORDER BY {sort_column} {sort_direction}, id {sort_direction} LIMIT "
+        ));
+        query.push_bind(limit);
+        query.push(" OFFSET ");
+        query.push_bind(offset);
+        let rows = query
+            .build_query_as::<ItemRow>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        // The total must use the same filters so pagination stays consistent
+        let total = self.count_items(tenant_id, params).await?;
+
+        Ok((rows.into_iter().map(item_from_row).collect(), total))
+    }
+
+    async fn count_items(&self, tenant_id: &str, params: &ListItemsParams) -> Result<i64, AppError> {
+        let created = params.created_range()?;
+        let name_pattern = params.name_pattern();
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM items");
+        push_list_filters(&mut count, tenant_id, params, name_pattern.as_deref(), created, None);
+        let total: i64 = count
+            .build_query_scalar()
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(total)
+    }
+
+    async fn update_item(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        name: Option<&str>,
+        value: Option<i64>,
+        ctx: &EventContext,
+    ) -> Result<Option<Item>, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let Some(old) = lock_item(&mut tx, tenant_id, id).await? else {
+            return Ok(None);
+        };
+        // updated_at is set explicitly here; there is no trigger maintaining it
+        let row = sqlx::query_as::<_, ItemRow>(
+            r#"
+            UPDATE items
+            SET name = COALESCE($3, name),
+                value = COALESCE($4, value),
+                updated_at = NOW()
+            WHERE tenant_id = $1 AND id = $2::uuid
+            RETURNING id::text, tenant_id, name, value, created_at, updated_at, metadata
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .bind(name)
+        .bind(value)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let item = item_from_row(row);
+
+        // Record the event with W3C trace context in the same transaction as the update
+        enqueue_outbox_event(&mut tx, &updated_event(&item, ctx), ctx).await?;
+        record_audit(&mut tx, tenant_id, &audit_entry(&item.id, AuditAction::Updated, Some(&old), Some(&item), ctx)).await?;
+        tx.commit().await?;
+
+        Ok(Some(item))
+    }
+
+    async fn increment_item(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        delta: i64,
+        (min, max): (i64, i64),
+        ctx: &EventContext,
+    ) -> Result<Option<Item>, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let Some(old) = lock_item(&mut tx, tenant_id, id).await? else {
+            return Ok(None);
+        };
+        // The bounds check happens in the same statement, in numeric so it cannot overflow
+        let row = sqlx::query_as::<_, ItemRow>(
+            r#"
+            UPDATE items
+            SET value = value + $3,
+                updated_at = NOW()
+            WHERE tenant_id = $1 AND id = $2::uuid
+              AND value::numeric + $3 BETWEEN $4 AND $5
+            RETURNING id::text, tenant_id, name, value, created_at, updated_at, metadata
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .bind(delta)
+        .bind(min)
+        .bind(max)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        // The item is locked and present, so nothing matching means the result is out of range
+        let Some(row) = row else {
+            return Err(ValidationError::ValueOutOfRange { min, max }.into());
+        };
+        let item = item_from_row(row);
+
+        // Record the event with W3C trace context in the same transaction as the update
+        enqueue_outbox_event(&mut tx, &updated_event(&item, ctx), ctx).await?;
+        record_audit(&mut tx, tenant_id, &audit_entry(&item.id, AuditAction::Updated, Some(&old), Some(&item), ctx)).await?;
+        tx.commit().await?;
+
+        Ok(Some(item))
+    }
+
+    async fn republish_item(&self, tenant_id: &str, id: &str, ctx: &EventContext) -> Result<Option<Item>, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let Some(row) = sqlx::query_as::<_, ItemRow>(
+            r#"
+            SELECT id::text, tenant_id, name, value, created_at, updated_at, metadata
+            FROM items
+            WHERE tenant_id = $1 AND id = $2::uuid
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let item = item_from_row(row);
+
+        // Replays go through the outbox too, so they are ordered after earlier events
+        enqueue_outbox_event(&mut tx, &created_event(&item, ctx, true), ctx).await?;
+        tx.commit().await?;
+
+        Ok(Some(item))
+    }
+
+    async fn delete_item(&self, tenant_id: &str, id: &str, ctx: &EventContext) -> Result<bool, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query_as::<_, ItemRow>(
+            r#"
+            DELETE FROM items
+            WHERE tenant_id = $1 AND id = $2::uuid
+            RETURNING id::text, tenant_id, name, value, created_at, updated_at, metadata
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(false);
+        };
+        let old = item_from_row(row);
+
+        release_idempotency_keys(&mut tx, tenant_id, std::slice::from_ref(&old.id)).await?;
+        // Record the event with W3C trace context in the same transaction as the delete
+        enqueue_outbox_event(&mut tx, &deleted_event(tenant_id, id, ctx), ctx).await?;
+        record_audit(&mut tx, tenant_id, &audit_entry(id, AuditAction::Deleted, Some(&old), None, ctx)).await?;
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    async fn delete_items_matching(
+        &self,
+        tenant_id: &str,
+        params: &ListItemsParams,
+        ctx: &EventContext,
+    ) -> Result<u64, AppError> {
+        let created = params.created_range()?;
+        let name_pattern = params.name_pattern();
+
+        let mut tx = self.pool.begin().await?;
+        let mut query = QueryBuilder::new("DELETE FROM items");
+        push_list_filters(&mut query, tenant_id, params, name_pattern.as_deref(), created, None);
+        query.push(" RETURNING id::text, tenant_id, name, value, created_at, updated_at, metadata");
+        let rows: Vec<ItemRow> = query.build_query_as().fetch_all(&mut *tx).await?;
+        let deleted: Vec<Item> = rows.into_iter().map(item_from_row).collect();
+        let ids: Vec<String> = deleted.iter().map(|item| item.id.clone()).collect();
+        release_idempotency_keys(&mut tx, tenant_id, &ids).await?;
+
+        // Per-item events and audit entries, so a purge is recorded like any other delete
+        for item in &deleted {
+            enqueue_outbox_event(&mut tx, &deleted_event(tenant_id, &item.id, ctx), ctx).await?;
+            record_audit(&mut tx, tenant_id, &audit_entry(&item.id, AuditAction::Deleted, Some(item), None, ctx)).await?;
+        }
+
+        tx.commit().await?;
+        Ok(deleted.len() as u64)
+    }
+
+    async fn item_history(&self, tenant_id: &str, id: &str) -> Result<Vec<AuditEntry>, AppError> {
+        let rows = sqlx::query_as::<_, AuditRow>(
+            r#"
+            SELECT item_id::text, action, old_value, new_value, actor, request_id, created_at
+            FROM item_audit
+            WHERE tenant_id = $1 AND item_id = $2::uuid
+            ORDER BY id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(audit_entry_from_row).collect()
+    }
+
+    async fn schema_ok(&self) -> Result<bool, AppError> {
+        let exists: bool = sqlx::query_scalar("SELECT to_regclass('items') IS NOT NULL")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(exists)
+    }
+}
+
+#[derive(Debug, Default)]
+struct MemoryStore {
+    items: Vec<Item>,
+    // (tenant_id, key) -> (item id, claimed at)
+    idempotency_keys: HashMap<(String, String), (String, Instant)>,
+    events: Vec<ItemEvent>,
+    // (tenant_id, entry), oldest first
+    audit: Vec<(String, AuditEntry)>,
+}
+
+// In-memory repository for tests; events are kept in a list instead of an outbox
+#[derive(Debug)]
+pub struct MemoryRepository {
+    store: Mutex<MemoryStore>,
+    idempotency_key_ttl: Duration,
+    unique_names: bool,
+}
+
+impl Default for MemoryRepository {
+    fn default() -> Self {
+        MemoryRepository::new(24 * 60 * 60)
+    }
+}
+
+impl MemoryRepository {
+    pub fn new(idempotency_key_ttl_secs: u64) -> Self {
+        MemoryRepository {
+            store: Mutex::new(MemoryStore::default()),
+            idempotency_key_ttl: Duration::from_secs(idempotency_key_ttl_secs),
+            unique_names: false,
+        }
+    }
+
+    // Reject duplicate names the way the Postgres unique name index does
+    pub fn enforce_unique_names(mut self, enforce: bool) -> Self {
+        self.unique_names = enforce;
+        self
+    }
+
+    fn check_unique_name(
+        &self,
+        store: &MemoryStore,
+        tenant_id: &str,
+        name: &str,
+        except_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        let taken = store
+            .items
+            .iter()
+            .any(|item| item.tenant_id == tenant_id && item.name == name && Some(item.id.as_str()) != except_id);
+        if self.unique_names && taken {
+            return Err(AppError::DuplicateName);
+        }
+        Ok(())
+    }
+
+    // Events recorded so far, oldest first
+    pub fn events(&self) -> Vec<ItemEvent> {
+        self.store.lock().unwrap().events.clone()
+    }
+
+    // In-memory equivalent of push_list_filters
+    fn matches_filters(item: &Item, params: &ListItemsParams, created: CreatedRange) -> bool {
+        params
+            .name_contains
+            .as_deref()
+            .is_none_or(|needle| item.name.to_lowercase().contains(&needle.to_lowercase()))
+            && params.min_value.is_none_or(|min| item.value >= min)
+            && params.max_value.is_none_or(|max| item.value <= max)
+            && created.contains(item.created_at)
+    }
+
+    fn tenant_items<'a>(store: &'a MemoryStore, tenant_id: &'a str) -> impl Iterator<Item = &'a Item> {
+        store.items.iter().filter(move |item| item.tenant_id == tenant_id)
+    }
+
+    fn tenant_item_mut<'a>(store: &'a mut MemoryStore, tenant_id: &str, id: &str) -> Option<&'a mut Item> {
+        store
+            .items
+            .iter_mut()
+            .find(|item| item.tenant_id == tenant_id && item.id == id)
+    }
+
+    fn new_item(tenant_id: &str, name: &str, value: i64, metadata: Option<serde_json::Value>) -> Item {
+        let now = Utc::now();
+        Item {
+            id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: tenant_id.to_string(),
+            name: name.to_string(),
+            value,
+            created_at: now,
+            updated_at: now,
+            metadata,
+        }
+    }
+
+    fn live_key_item(&self, store: &MemoryStore, tenant_id: &str, key: &str) -> Option<Item> {
+        let (item_id, claimed_at) = store
+            .idempotency_keys
+            .get(&(tenant_id.to_string(), key.to_string()))?;
+        if claimed_at.elapsed() >= self.idempotency_key_ttl {
+            return None;
+        }
+        Self::tenant_items(store, tenant_id).find(|item| &item.id == item_id).cloned()
+    }
+}
+
+#[async_trait]
+impl Repository for MemoryRepository {
+    async fn create_item(
+        &self,
+        tenant_id: &str,
+        name: &str,
+        value: i64,
+        metadata: Option<&serde_json::Value>,
+        idempotency_key: Option<&str>,
+        ctx: &EventContext,
+    ) -> Result<CreateOutcome, AppError> {
+        let mut store = self.store.lock().unwrap();
+
+        if let Some(key) = idempotency_key
+            && let Some(existing) = self.live_key_item(&store, tenant_id, key)
+        {
+            return Ok(CreateOutcome::Existing(existing));
+        }
+
+        self.check_unique_name(&store, tenant_id, name, None)?;
+        let item = Self::new_item(tenant_id, name, value, metadata.cloned());
+        if let Some(key) = idempotency_key {
+            store
+                .idempotency_keys
+                .insert((tenant_id.to_string(), key.to_string()), (item.id.clone(), Instant::now()));
+        }
+        store.events.push(created_event(&item, ctx, false));
+        store.audit.push((tenant_id.to_string(), audit_entry(&item.id, AuditAction::Created, None, Some(&item), ctx)));
+        store.items.push(item.clone());
+
+        Ok(CreateOutcome::Created(item))
+    }
+
+    async fn create_items(
+        &self,
+        tenant_id: &str,
+        items: &[(String, i64, Option<serde_json::Value>)],
+        ctx: &EventContext,
+    ) -> Result<Vec<Item>, AppError> {
+        let mut store = self.store.lock().unwrap();
+
+        let items: Vec<Item> = items
+            .iter()
+            .map(|(name, value, metadata)| Self::new_item(tenant_id, name, *value, metadata.clone()))
+            .collect();
+        // The whole batch fails like the single Postgres insert would
+        for (index, item) in items.iter().enumerate() {
+            self.check_unique_name(&store, tenant_id, &item.name, None)?;
+            if self.unique_names && items[..index].iter().any(|other| other.name == item.name) {
+                return Err(AppError::DuplicateName);
+            }
+        }
+        for item in &items {
+            store.events.push(created_event(item, ctx, false));
+            store.audit.push((tenant_id.to_string(), audit_entry(&item.id, AuditAction::Created, None, Some(item), ctx)));
+            store.items.push(item.clone());
+        }
+
+        Ok(items)
+    }
+
+    async fn find_by_idempotency_key(&self, tenant_id: &str, key: &str) -> Result<Option<Item>, AppError> {
+        let store = self.store.lock().unwrap();
+        Ok(self.live_key_item(&store, tenant_id, key))
+    }
+
+    async fn get_item(&self, tenant_id: &str, id: &str) -> Result<Option<Item>, AppError> {
+        let store = self.store.lock().unwrap();
+        Ok(Self::tenant_items(&store, tenant_id).find(|item| item.id == id).cloned())
+    }
+
+    async fn item_exists(&self, tenant_id: &str, id: &str) -> Result<bool, AppError> {
+        let store = self.store.lock().unwrap();
+        Ok(Self::tenant_items(&store, tenant_id).any(|item| item.id == id))
+    }
+
+    async fn get_items(&self, tenant_id: &str, ids: &[String]) -> Result<Vec<Item>, AppError> {
+        let store = self.store.lock().unwrap();
+        Ok(Self::tenant_items(&store, tenant_id)
+            .filter(|item| ids.contains(&item.id))
+            .cloned()
+            .collect())
+    }
+
+    async fn list_items(
+        &self,
+        tenant_id: &str,
+        params: &ListItemsParams,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<Item>, i64), AppError> {
+        let (sort_column, sort_direction) = params.sort()?;
+        let after = params.after()?;
+        let created = params.created_range()?;
+
+        let store = self.store.lock().unwrap();
+        let mut items: Vec<Item> = Self::tenant_items(&store, tenant_id)
+            .filter(|item| Self::matches_filters(item, params, created))
+            .filter(|item| {
+                after
+                    .as_ref()
+                    .is_none_or(|cursor| (item.created_at, &item.id) < (cursor.created_at, &cursor.id))
+            })
+            .cloned()
+            .collect();
+
+        items.sort_by(|a, b| {
+            let ordering = match sort_column {
+                "name" => a.name.cmp(&b.name),
+                "value" => a.value.cmp(&b.value),
+                _ => a.created_at.cmp(&b.created_at),
+            }
+            .then_with(|| a.id.cmp(&b.id));
+            if sort_direction == "DESC" { ordering.reverse() } else { ordering }
+        });
+
+        // Like the Postgres count, the total ignores the cursor
+        let total = Self::tenant_items(&store, tenant_id)
+            .filter(|item| Self::matches_filters(item, params, created))
+            .count() as i64;
+        let page = items
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect();
+
+        Ok((page, total))
+    }
+
+    async fn count_items(&self, tenant_id: &str, params: &ListItemsParams) -> Result<i64, AppError> {
+        let created = params.created_range()?;
+        let store = self.store.lock().unwrap();
+        let count = Self::tenant_items(&store, tenant_id)
+            .filter(|item| Self::matches_filters(item, params, created))
+            .count();
+        Ok(count as i64)
+    }
+
+    async fn update_item(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        name: Option<&str>,
+        value: Option<i64>,
+        ctx: &EventContext,
+    ) -> Result<Option<Item>, AppError> {
+        let mut store = self.store.lock().unwrap();
+
+        if let Some(name) = name {
+            self.check_unique_name(&store, tenant_id, name, Some(id))?;
+        }
+        let Some(item) = Self::tenant_item_mut(&mut store, tenant_id, id) else {
+            return Ok(None);
+        };
+        let old = item.clone();
+        if let Some(name) = name {
+            item.name = name.to_string();
+        }
+        if let Some(value) = value {
+            item.value = value;
+        }
+        item.updated_at = Utc::now();
+        let item = item.clone();
+
+        store.events.push(updated_event(&item, ctx));
+        store.audit.push((tenant_id.to_string(), audit_entry(id, AuditAction::Updated, Some(&old), Some(&item), ctx)));
+        Ok(Some(item))
+    }
+
+    async fn increment_item(
+        &self,
+        tenant_id: &str,
+        id: &str,
+        delta: i64,
+        (min, max): (i64, i64),
+        ctx: &EventContext,
+    ) -> Result<Option<Item>, AppError> {
+        let mut store = self.store.lock().unwrap();
+
+        let Some(item) = Self::tenant_item_mut(&mut store, tenant_id, id) else {
+            return Ok(None);
+        };
+        let old = item.clone();
+        let value = item
+            .value
+            .checked_add(delta)
+            .filter(|value| (min..=max).contains(value))
+            .ok_or(ValidationError::ValueOutOfRange { min, max })?;
+        item.value = value;
+        item.updated_at = Utc::now();
+        let item = item.clone();
+
+        store.events.push(updated_event(&item, ctx));
+        store.audit.push((tenant_id.to_string(), audit_entry(id, AuditAction::Updated, Some(&old), Some(&item), ctx)));
+        Ok(Some(item))
+    }
+
+    async fn republish_item(&self, tenant_id: &str, id: &str, ctx: &EventContext) -> Result<Option<Item>, AppError> {
+        let mut store = self.store.lock().unwrap();
+
+        let Some(item) = Self::tenant_items(&store, tenant_id).find(|item| item.id == id).cloned() else {
+            return Ok(None);
+        };
+        store.events.push(created_event(&item, ctx, true));
+        Ok(Some(item))
+    }
+
+    async fn delete_item(&self, tenant_id: &str, id: &str, ctx: &EventContext) -> Result<bool, AppError> {
+        let mut store = self.store.lock().unwrap();
+
+        let Some(index) = store
+            .items
+            .iter()
+            .position(|item| item.tenant_id == tenant_id && item.id == id)
+        else {
+            return Ok(false);
+        };
+        let old = store.items.remove(index);
+        store.idempotency_keys.retain(|(tenant, _), (item_id, _)| tenant != tenant_id || item_id != id);
+
+        store.events.push(deleted_event(tenant_id, id, ctx));
+        store.audit.push((tenant_id.to_string(), audit_entry(id, AuditAction::Deleted, Some(&old), None, ctx)));
+        Ok(true)
+    }
+
+    async fn delete_items_matching(
+        &self,
+        tenant_id: &str,
+        params: &ListItemsParams,
+        ctx: &EventContext,
+    ) -> Result<u64, AppError> {
+        let created = params.created_range()?;
+        let mut store = self.store.lock().unwrap();
+
+        let (deleted, kept): (Vec<Item>, Vec<Item>) = std::mem::take(&mut store.items)
+            .into_iter()
+            .partition(|item| item.tenant_id == tenant_id && Self::matches_filters(item, params, created));
+        store.items = kept;
+        store
+            .idempotency_keys
+            .retain(|(tenant, _), (item_id, _)| tenant != tenant_id || !deleted.iter().any(|item| &item.id == item_id));
+
+        for item in &deleted {
+            store.events.push(deleted_event(tenant_id, &item.id, ctx));
+            store.audit.push((tenant_id.to_string(), audit_entry(&item.id, AuditAction::Deleted, Some(item), None, ctx)));
+        }
+        Ok(deleted.len() as u64)
+    }
+
+    async fn item_history(&self, tenant_id: &str, id: &str) -> Result<Vec<AuditEntry>, AppError> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .audit
+            .iter()
+            .filter(|(tenant, entry)| tenant == tenant_id && entry.item_id == id)
+            .map(|(_, entry)| entry.clone())
+            .collect())
+    }
+
+    async fn schema_ok(&self) -> Result<bool, AppError> {
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TENANT: &str = "acme";
+
+    #[test]
+    fn test_list_filters_are_bound_parameters() {
+        let params = ListItemsParams {
+            name_contains: Some("'; DROP TABLE items; --".to_string()),
+            min_value: Some(10),
+            max_value: Some(20),
+            ..Default::default()
+        };
+        let pattern = params.name_pattern();
+
+        let mut query = QueryBuilder::new("SELECT COUNT(*) FROM items");
+        push_list_filters(&mut query, TENANT, &params, pattern.as_deref(), CreatedRange::default(), None);
+        assert_eq!(
+            query.sql(),
+            "SELECT COUNT(*) FROM items WHERE tenant_id = $1 AND name ILIKE $2 AND value >= $3 AND value <= $4"
+        );
+    }
+
+    #[test]
+    fn test_list_filters_only_value_bound() {
+        let params = ListItemsParams { max_value: Some(20), ..Default::default() };
+
+        let mut query = QueryBuilder::new("SELECT COUNT(*) FROM items");
+        push_list_filters(&mut query, TENANT, &params, None, CreatedRange::default(), None);
+        assert_eq!(query.sql(), "SELECT COUNT(*) FROM items WHERE tenant_id = $1 AND value <= $2");
+    }
+
+    #[test]
+    fn test_list_filters_keyset_condition() {
+        let params = ListItemsParams { min_value: Some(1), ..Default::default() };
+        let cursor = ItemCursor {
+            created_at: Utc::now(),
+            id: "6f1c2b7e-3d4a-4b5c-8d9e-0f1a2b3c4d5e".to_string(),
+        };
+
+        let mut query = QueryBuilder::new("SELECT COUNT(*) FROM items");
+        push_list_filters(&mut query, TENANT, &params, None, CreatedRange::default(), Some(&cursor));
+        assert_eq!(
+            query.sql(),
+            "SELECT COUNT(*) FROM items WHERE tenant_id = $1 AND value >= $2 AND (created_at, id) < ($3, $4::uuid)"
+        );
+    }
+
+    #[test]
+    fn test_items_schema_mismatches() {
+        let mut columns: Vec<(String, String)> = ITEMS_COLUMNS
+            .iter()
+            .map(|(column, data_type)| (column.to_string(), data_type.to_string()))
+            .collect();
+        columns.push(("legacy_flag".to_string(), "boolean".to_string()));
+        assert!(items_schema_mismatches(&columns).is_empty());
+
+        columns.retain(|(column, _)| column != "updated_at");
+        columns.iter_mut().find(|(column, _)| column == "value").unwrap().1 = "integer".to_string();
+        assert_eq!(
+            items_schema_mismatches(&columns),
+            ["column value is integer, expected bigint", "column updated_at is missing"]
+        );
+    }
+
+    #[test]
+    fn test_list_filters_created_range() {
+        let params = ListItemsParams::default();
+        let created = CreatedRange {
+            from: Some("2024-01-01T00:00:00Z".parse().unwrap()),
+            to: Some("2024-02-01T00:00:00Z".parse().unwrap()),
+        };
+
+        let mut query = QueryBuilder::new("SELECT COUNT(*) FROM items");
+        push_list_filters(&mut query, TENANT, &params, None, created, None);
+        assert_eq!(
+            query.sql(),
+            "SELECT COUNT(*) FROM items WHERE tenant_id = $1 AND created_at >= $2 AND created_at < $3"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_memory_idempotency_key_returns_existing_item() {
+        let repo = MemoryRepository::default();
+        let ctx = EventContext::default();
+
+        let CreateOutcome::Created(first) = repo.create_item(TENANT, "a", 1, None, Some("key"), &ctx).await.unwrap() else {
+            panic!("first create should insert");
+        };
+        let CreateOutcome::Existing(again) = repo.create_item(TENANT, "b", 2, None, Some("key"), &ctx).await.unwrap() else {
+            panic!("second create should reuse the key");
+        };
+
+        assert_eq!(again.id, first.id);
+        assert_eq!(repo.events().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_memory_unique_names() {
+        let repo = MemoryRepository::default().enforce_unique_names(true);
+        let ctx = EventContext::default();
+
+        repo.create_item(TENANT, "a", 1, None, None, &ctx).await.unwrap();
+        let CreateOutcome::Created(b) = repo.create_item(TENANT, "b", 2, None, None, &ctx).await.unwrap() else {
+            panic!("distinct name should insert");
+        };
+        assert!(matches!(repo.create_item(TENANT, "a", 3, None, None, &ctx).await, Err(AppError::DuplicateName)));
+        assert!(matches!(
+            repo.update_item(TENANT, &b.id, Some("a"), None, &ctx).await,
+            Err(AppError::DuplicateName)
+        ));
+        // Renaming an item to its own name is not a conflict
+        assert!(repo.update_item(TENANT, &b.id, Some("b"), None, &ctx).await.unwrap().is_some());
+        assert!(matches!(
+            repo.create_items(TENANT, &[("c".to_string(), 1, None), ("c".to_string(), 2, None)], &ctx).await,
+            Err(AppError::DuplicateName)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_memory_increment_respects_bounds() {
+        let repo = MemoryRepository::default();
+        let ctx = EventContext::default();
+        let CreateOutcome::Created(item) = repo.create_item(TENANT, "a", 5, None, None, &ctx).await.unwrap() else {
+            panic!("create should insert");
+        };
+
+        let bumped = repo.increment_item(TENANT, &item.id, 3, (0, 10), &ctx).await.unwrap().unwrap();
+        assert_eq!(bumped.value, 8);
+        assert!(matches!(
+            repo.increment_item(TENANT, &item.id, 3, (0, 10), &ctx).await,
+            Err(AppError::Validation(ValidationError::ValueOutOfRange { min: 0, max: 10 }))
+        ));
+        assert!(matches!(
+            repo.increment_item(TENANT, &item.id, i64::MAX, (0, i64::MAX), &ctx).await,
+            Err(AppError::Validation(_))
+        ));
+        assert!(repo.increment_item(TENANT, "missing", 1, (0, 10), &ctx).await.unwrap().is_none());
+        assert_eq!(repo.get_item(TENANT, &item.id).await.unwrap().unwrap().value, 8);
+    }
+
+    #[tokio::test]
+    async fn test_memory_audit_covers_bulk_deletes_but_not_rejected_writes() {
+        let repo = MemoryRepository::default();
+        let ctx = EventContext {
+            actor: Some("ops".to_string()),
+            ..Default::default()
+        };
+        let CreateOutcome::Created(item) = repo.create_item(TENANT, "old", 5, None, None, &ctx).await.unwrap() else {
+            panic!("create should insert");
+        };
+        assert!(repo.increment_item(TENANT, &item.id, 100, (0, 10), &ctx).await.is_err());
+
+        let params = ListItemsParams {
+            name_contains: Some("old".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(repo.delete_items_matching(TENANT, &params, &ctx).await.unwrap(), 1);
+
+        let history = repo.item_history(TENANT, &item.id).await.unwrap();
+        let actions: Vec<AuditAction> = history.iter().map(|entry| entry.action).collect();
+        assert_eq!(actions, [AuditAction::Created, AuditAction::Deleted]);
+        assert!(history.iter().all(|entry| entry.actor == "ops"));
+        assert_eq!(history[1].old_value.as_ref().unwrap()["value"], 5);
+        assert!(repo.item_history("globex", &item.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_memory_tenants_are_isolated() {
+        let repo = MemoryRepository::default().enforce_unique_names(true);
+        let ctx = EventContext::default();
+
+        let CreateOutcome::Created(item) = repo.create_item(TENANT, "a", 1, None, Some("key"), &ctx).await.unwrap() else {
+            panic!("create should insert");
+        };
+        // Another tenant may reuse both the name and the idempotency key
+        let CreateOutcome::Created(other) = repo.create_item("globex", "a", 2, None, Some("key"), &ctx).await.unwrap() else {
+            panic!("other tenant's create should insert");
+        };
+        assert_ne!(other.id, item.id);
+
+        assert!(repo.get_item("globex", &item.id).await.unwrap().is_none());
+        assert!(repo.get_items("globex", std::slice::from_ref(&item.id)).await.unwrap().is_empty());
+        assert!(repo.update_item("globex", &item.id, None, Some(5), &ctx).await.unwrap().is_none());
+        assert!(!repo.delete_item("globex", &item.id, &ctx).await.unwrap());
+        assert_eq!(repo.count_items(TENANT, &ListItemsParams::default()).await.unwrap(), 1);
+
+        let (items, total) = repo.list_items("globex", &ListItemsParams::default(), 10, 0).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(items[0].id, other.id);
+        assert_eq!(repo.events().last().unwrap().tenant_id(), "globex");
+    }
+
+    #[tokio::test]
+    async fn test_memory_expired_idempotency_key_is_reused() {
+        let repo = MemoryRepository::new(0);
+        let ctx = EventContext::default();
+
+        repo.create_item(TENANT, "a", 1, None, Some("key"), &ctx).await.unwrap();
+        assert!(repo.find_by_idempotency_key(TENANT, "key").await.unwrap().is_none());
+        assert!(matches!(
+            repo.create_item(TENANT, "b", 2, None, Some("key"), &ctx).await.unwrap(),
+            CreateOutcome::Created(_)
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::Context;
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::Sampler;
+use rdkafka::message::{Headers, Message};
+
+use crate::config::{Config, OtlpProtocol};
+
+// Build the OTLP span exporter for the configured protocol
+pub fn span_exporter(config: &Config) -> anyhow::Result<SpanExporter> {
+    match config.otlp_protocol {
+        OtlpProtocol::Grpc => SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(&config.otlp_endpoint)
+            .build()
+            .context("Failed to create OTLP gRPC exporter"),
+        OtlpProtocol::Http => SpanExporter::builder()
+            .with_http()
+            .with_endpoint(http_signal_endpoint(&config.otlp_endpoint, "traces"))
+            .build()
+            .context("Failed to create OTLP HTTP exporter"),
+    }
+}
+
+// Build the OTLP metric exporter; it shares the span exporter's endpoint and protocol
+pub fn metric_exporter(config: &Config) -> anyhow::Result<MetricExporter> {
+    match config.otlp_protocol {
+        OtlpProtocol::Grpc => MetricExporter::builder()
+            .with_tonic()
+            .with_endpoint(&config.otlp_endpoint)
+            .build()
+            .context("Failed to create OTLP gRPC metric exporter"),
+        OtlpProtocol::Http => MetricExporter::builder()
+            .with_http()
+            .with_endpoint(http_signal_endpoint(&config.otlp_endpoint, "metrics"))
+            .build()
+            .context("Failed to create OTLP HTTP metric exporter"),
+    }
+}
+
+// Follow the caller's sampling decision when there is one, otherwise sample
+// root traces at the configured ratio
+pub fn sampler(config: &Config) -> Sampler {
+    Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(config.trace_sampler_ratio)))
+}
+
+// Read W3C trace headers from a Kafka message
+pub struct KafkaHeaderExtractor<'a, M>(pub &'a M);
+
+impl<M: Message> Extractor for KafkaHeaderExtractor<'_, M> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .headers()?
+            .iter()
+            .find(|header| header.key.eq_ignore_ascii_case(key))
+            .and_then(|header| header.value)
+            .and_then(|value| std::str::from_utf8(value).ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0
+            .headers()
+            .map(|headers| headers.iter().map(|header| header.key).collect())
+            .unwrap_or_default()
+    }
+}
+
+// Producer's trace context from a message's traceparent/tracestate headers, so a
+// consumer span can continue the trace of the request that emitted the event
+pub fn kafka_parent_context<M: Message>(message: &M) -> opentelemetry::Context {
+    TraceContextPropagator::new().extract(&KafkaHeaderExtractor(message))
+}
+
+// The HTTP exporters post to the endpoint as given, so append the signal's path
+// (traces or metrics) to a bare collector URL the way the OTLP spec does for the
+// generic env var
+fn http_signal_endpoint(endpoint: &str, signal: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    let path = format!("/v1/{}", signal);
+    if endpoint.ends_with(&path) {
+        endpoint.to_string()
+    } else {
+        format!("{}{}", endpoint, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_signal_endpoint() {
+        assert_eq!(http_signal_endpoint("http://collector:4318", "traces"), "http://collector:4318/v1/traces");
+        assert_eq!(http_signal_endpoint("http://collector:4318/", "traces"), "http://collector:4318/v1/traces");
+        assert_eq!(
+            http_signal_endpoint("http://collector:4318/v1/traces", "traces"),
+            "http://collector:4318/v1/traces"
+        );
+        assert_eq!(http_signal_endpoint("http://collector:4318", "metrics"), "http://collector:4318/v1/metrics");
+    }
+}
@@ -1,26 +1,30 @@
 // Simple API test that can run without Docker
-// Tests the models and basic validation
+// Tests the models and basic validation, plus request flows over the in-memory repository
 
 #[test]
 fn test_item_validation() {
     use home_task::Item;
 
+    // NAME_MAX_LEN default
+    let max_len = 255;
+
     // Valid name
-    assert!(Item::validate_name("Test Item").is_ok());
+    assert!(Item::validate_name("Test Item", max_len).is_ok());
 
     // Empty name
-    assert!(Item::validate_name("").is_err());
+    assert!(Item::validate_name("", max_len).is_err());
     assert_eq!(
-        Item::validate_name("").unwrap_err(),
+        Item::validate_name("", max_len).unwrap_err().to_string(),
         "name cannot be empty"
     );
+    assert_eq!(Item::validate_name("", max_len).unwrap_err().code(), "name_empty");
 
     // Whitespace only
-    assert!(Item::validate_name("   ").is_err());
+    assert!(Item::validate_name("   ", max_len).is_err());
 
     // Too long
     let long_name = "a".repeat(256);
-    assert!(Item::validate_name(&long_name).is_err());
+    assert!(Item::validate_name(&long_name, max_len).is_err());
 }
 
 #[test]
@@ -48,10 +52,124 @@ fn test_item_event_serialization() {
     let event: ItemEvent = serde_json::from_str(json).unwrap();
 
     match event {
-        ItemEvent::Created { id, name, value, .. } => {
+        ItemEvent::Created { id, name, value, created_at, .. } => {
             assert_eq!(id, "123");
             assert_eq!(name, "Test");
             assert_eq!(value, 42);
+            assert_eq!(created_at.to_rfc3339(), "2024-01-01T00:00:00+00:00");
         }
+        other => panic!("expected Created event, got {:?}", other),
     }
 }
+
+#[test]
+fn test_update_item_request_serialization() {
+    use home_task::UpdateItemRequest;
+
+    // Both fields
+    let json = r#"{"name":"Renamed","value":7}"#;
+    let req: UpdateItemRequest = serde_json::from_str(json).unwrap();
+    assert_eq!(req.name.as_deref(), Some("Renamed"));
+    assert_eq!(req.value, Some(7));
+
+    // Partial update
+    let json = r#"{"value":7}"#;
+    let req: UpdateItemRequest = serde_json::from_str(json).unwrap();
+    assert_eq!(req.name, None);
+    assert_eq!(req.value, Some(7));
+}
+
+#[test]
+fn test_item_updated_event_serialization() {
+    use home_task::ItemEvent;
+
+    let event = ItemEvent::Updated {
+        id: "123".to_string(),
+        tenant_id: "acme".to_string(),
+        name: "Renamed".to_string(),
+        value: 7,
+        created_at: "2024-01-01T00:00:00Z".parse().unwrap(),
+        request_id: Some("req-1".to_string()),
+    };
+
+    let json = serde_json::to_value(&event).unwrap();
+    assert_eq!(json["type"], "item_updated");
+    assert_eq!(json["id"], "123");
+    assert_eq!(json["name"], "Renamed");
+    assert_eq!(json["value"], 7);
+    assert_eq!(json["created_at"], "2024-01-01T00:00:00Z");
+    assert_eq!(json["request_id"], "req-1");
+    assert_eq!(json["tenant_id"], "acme");
+}
+
+#[test]
+fn test_item_deleted_event_serialization() {
+    use home_task::ItemEvent;
+
+    let json = r#"{"type":"item_deleted","id":"123","deleted_at":"2024-01-01T00:00:00Z"}"#;
+    let event: ItemEvent = serde_json::from_str(json).unwrap();
+
+    match event {
+        ItemEvent::Deleted { id, tenant_id, deleted_at, request_id } => {
+            assert_eq!(id, "123");
+            // Events queued before tenancy existed belong to the default tenant
+            assert_eq!(tenant_id, home_task::DEFAULT_TENANT);
+            assert_eq!(deleted_at, "2024-01-01T00:00:00Z");
+            assert_eq!(request_id, None);
+        }
+        other => panic!("expected Deleted event, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_item_flow_against_memory_repository() {
+    use home_task::{CreateOutcome, EventContext, ItemEvent, ListItemsParams, MemoryRepository, Repository};
+
+    let repo = MemoryRepository::default();
+    let ctx = EventContext {
+        request_id: Some("req-1".to_string()),
+        ..Default::default()
+    };
+
+    let CreateOutcome::Created(item) = repo.create_item("acme", "Widget", 5, None, Some("key-1"), &ctx).await.unwrap() else {
+        panic!("expected a new item");
+    };
+    let batch = repo
+        .create_items("acme", &[("Gadget".to_string(), 50, None), ("widget mini".to_string(), 1, None)], &ctx)
+        .await
+        .unwrap();
+    assert_eq!(batch.len(), 2);
+
+    // Retrying with the same idempotency key returns the original item
+    match repo.create_item("acme", "Other", 6, None, Some("key-1"), &ctx).await.unwrap() {
+        CreateOutcome::Existing(existing) => assert_eq!(existing.id, item.id),
+        other => panic!("expected existing item, got {:?}", other),
+    }
+
+    let params = ListItemsParams {
+        name_contains: Some("WIDGET".to_string()),
+        sort_by: Some("value".to_string()),
+        sort_dir: Some("asc".to_string()),
+        ..Default::default()
+    };
+    let (items, total) = repo.list_items("acme", &params, 10, 0).await.unwrap();
+    assert_eq!(total, 2);
+    assert_eq!(items[0].name, "widget mini");
+    assert_eq!(items[1].id, item.id);
+
+    let updated = repo.update_item("acme", &item.id, None, Some(9), &ctx).await.unwrap().unwrap();
+    assert_eq!(updated.name, "Widget");
+    assert_eq!(updated.value, 9);
+    assert!(updated.updated_at >= item.updated_at);
+
+    assert!(repo.delete_item("acme", &item.id, &ctx).await.unwrap());
+    assert!(!repo.delete_item("acme", &item.id, &ctx).await.unwrap());
+    assert!(repo.get_item("acme", &item.id).await.unwrap().is_none());
+    assert!(repo.update_item("acme", &item.id, Some("Gone"), None, &ctx).await.unwrap().is_none());
+
+    // One event per successful write, tagged with the request id
+    let events = repo.events();
+    assert_eq!(events.len(), 5);
+    assert!(matches!(&events[3], ItemEvent::Updated { value: 9, .. }));
+    assert!(matches!(&events[4], ItemEvent::Deleted { request_id: Some(r), .. } if r == "req-1"));
+}